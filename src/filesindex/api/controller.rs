@@ -1,5 +1,12 @@
-use crate::filesindex::infrastructure::searchindex::{
-    models::search_params_model::SearchParamsModel, service::SearchIndexService,
+use crate::{
+    filesindex::infrastructure::searchindex::{
+        parsers::query_string_parser::{parse_query_string, quote_term},
+        service::SearchIndexService,
+    },
+    shared::{
+        models::search_params_model::SearchParamsModel,
+        suggestions::name_suggestions::suggest_names,
+    },
 };
 use actix_web::{
     dev::{ServiceFactory, ServiceRequest},
@@ -7,25 +14,37 @@ use actix_web::{
     App, Error, HttpResponse, Responder,
 };
 use futures::future;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
+use tantivy::IndexReader;
 use tokio::sync::{mpsc::Sender, Mutex};
 
 use super::dtos::{
-    input::{self, file_dto_input::FileDTOInput, query_dto_input::QueryDTOInput},
+    input::{
+        self, explain_dto_input::ExplainDTOInput, file_dto_input::FileDTOInput,
+        query_dto_input::QueryDTOInput, similar_dto_input::SimilarDTOInput,
+        suggest_dto_input::SuggestDTOInput,
+    },
     output::file_dto_output::FileDTOOutput,
 };
 
 pub struct FilesIndexController {
     service: Arc<Mutex<SearchIndexService>>,
     sender: Arc<Mutex<Sender<FileDTOInput>>>,
+    // Shared with the service, for the routes that only read the index
+    index_reader: Arc<RwLock<IndexReader>>,
 }
 
 impl FilesIndexController {
     pub fn new(
         service: Arc<Mutex<SearchIndexService>>,
         sender: Arc<Mutex<Sender<FileDTOInput>>>,
+        index_reader: Arc<RwLock<IndexReader>>,
     ) -> Self {
-        Self { service, sender }
+        Self {
+            service,
+            sender,
+            index_reader,
+        }
     }
 
     async fn index_files(
@@ -61,19 +80,85 @@ impl FilesIndexController {
     }
 
     async fn query(self: Arc<Self>, dto: web::Json<SearchParamsModel>) -> impl Responder {
+        self.run_query(&dto).await
+    }
+
+    async fn run_query(&self, params: &SearchParamsModel) -> HttpResponse {
         println!("locking service");
         let service = self.service.lock().await;
-        let result = match service.advanced_query(&dto) {
+        let result = match service.advanced_query(params) {
             Ok(result) => result,
             Err(err) => {
                 println!("failed to execute query: {}", err);
-                return HttpResponse::BadRequest().body("Failed to execute query.");
+                return HttpResponse::BadRequest()
+                    .body(format!("Failed to execute query: {}", err));
             }
         };
-        println!("completed query with {} results", result.len());
+        println!(
+            "completed query with {} of {} results",
+            result.results.len(),
+            result.total_hits
+        );
         HttpResponse::Ok().json(result)
     }
 
+    async fn query_text(self: Arc<Self>, dto: web::Json<QueryDTOInput>) -> impl Responder {
+        let dto = dto.into_inner();
+        let mut params = match parse_query_string(&dto.query) {
+            Ok(params) => params,
+            Err(err) => {
+                println!("failed to parse query: {}", err);
+                return HttpResponse::BadRequest().json(err);
+            }
+        };
+        if !dto.search_term.trim().is_empty() {
+            // Quoted so that the search term is never read as query syntax
+            let search_term = quote_term(&dto.search_term);
+            params.name = Some(match params.name {
+                Some(name) => format!("{} {}", name, search_term),
+                None => search_term,
+            });
+        }
+        params.limit = dto.limit;
+        params.offset = dto.offset;
+        params.highlight = dto.highlight;
+
+        self.run_query(&params).await
+    }
+
+    async fn explain(self: Arc<Self>, dto: web::Json<ExplainDTOInput>) -> impl Responder {
+        let service = self.service.lock().await;
+        match service.explain(&dto.params, &dto.file_path) {
+            Ok(explanation) => HttpResponse::Ok().json(explanation),
+            Err(err) => {
+                println!("failed to explain query: {}", err);
+                HttpResponse::BadRequest().body(format!("Failed to explain query: {}", err))
+            }
+        }
+    }
+
+    async fn suggest(self: Arc<Self>, dto: web::Json<SuggestDTOInput>) -> impl Responder {
+        let searcher = self.index_reader.read().unwrap().searcher();
+        match suggest_names(&searcher, &dto.partial, dto.get_limit()) {
+            Ok(suggestions) => HttpResponse::Ok().json(suggestions),
+            Err(err) => {
+                println!("failed to suggest names: {}", err);
+                HttpResponse::BadRequest().body(format!("Failed to suggest names: {}", err))
+            }
+        }
+    }
+
+    async fn similar(self: Arc<Self>, dto: web::Json<SimilarDTOInput>) -> impl Responder {
+        let service = self.service.lock().await;
+        match service.similar_files(&dto.file_path, dto.get_limit()) {
+            Ok(files) => HttpResponse::Ok().json(files),
+            Err(err) => {
+                println!("failed to find similar files: {}", err);
+                HttpResponse::BadRequest().body(format!("Failed to find similar files: {}", err))
+            }
+        }
+    }
+
     pub fn map_routes(self: Arc<Self>, cfg: &mut ServiceConfig) {
         cfg.route(
             "/index-files",
@@ -96,6 +181,46 @@ impl FilesIndexController {
                     async move { self_clone.query(dtos).await }
                 }
             }),
+        )
+        .route(
+            "/query/text",
+            web::post().to({
+                let self_clone = Arc::clone(&self); // Cloning the Arc to avoid moving
+                move |dto| {
+                    let self_clone = Arc::clone(&self_clone);
+                    async move { self_clone.query_text(dto).await }
+                }
+            }),
+        )
+        .route(
+            "/query/explain",
+            web::post().to({
+                let self_clone = Arc::clone(&self); // Cloning the Arc to avoid moving
+                move |dto| {
+                    let self_clone = Arc::clone(&self_clone);
+                    async move { self_clone.explain(dto).await }
+                }
+            }),
+        )
+        .route(
+            "/suggest",
+            web::post().to({
+                let self_clone = Arc::clone(&self); // Cloning the Arc to avoid moving
+                move |dto| {
+                    let self_clone = Arc::clone(&self_clone);
+                    async move { self_clone.suggest(dto).await }
+                }
+            }),
+        )
+        .route(
+            "/similar",
+            web::post().to({
+                let self_clone = Arc::clone(&self); // Cloning the Arc to avoid moving
+                move |dto| {
+                    let self_clone = Arc::clone(&self_clone);
+                    async move { self_clone.similar(dto).await }
+                }
+            }),
         );
     }
 }
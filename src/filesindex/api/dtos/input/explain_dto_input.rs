@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+use crate::shared::models::search_params_model::SearchParamsModel;
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ExplainDTOInput {
+    /// The path of the file whose ranking is explained
+    pub file_path: String,
+    /// The search that the file was ranked for
+    pub params: SearchParamsModel,
+}
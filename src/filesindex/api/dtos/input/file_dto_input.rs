@@ -10,5 +10,7 @@ pub struct FileDTOInput {
     pub metadata: String,
     pub date_modified: u64, // UNIX timestamp
     pub popularity:f64,
+    #[serde(default)]
+    pub size: u64, // bytes
 }
 
@@ -1,2 +1,5 @@
+pub mod explain_dto_input;
 pub mod file_dto_input;
-pub mod query_dto_input;
\ No newline at end of file
+pub mod query_dto_input;
+pub mod similar_dto_input;
+pub mod suggest_dto_input;
\ No newline at end of file
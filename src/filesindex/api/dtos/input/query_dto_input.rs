@@ -1,9 +1,15 @@
 use serde::{Deserialize, Serialize};
 
-
 #[derive(Clone, Deserialize, Serialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct QueryDTOInput {
-    pub search_term:String,
-    pub query:String
+    /// Plain text matched against file names as-is, without parsing any query syntax
+    #[serde(default)]
+    pub search_term: String,
+    /// Query string such as `ext:pdf size:>100MB modified:<2024-01-01 in:/home/me/docs -name:tmp`
+    pub query: String,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+    #[serde(default)]
+    pub highlight: bool,
 }
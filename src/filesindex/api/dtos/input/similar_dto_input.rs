@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+use crate::shared::models::search_params_model::{DEFAULT_LIMIT, MAX_LIMIT};
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SimilarDTOInput {
+    /// The path of an indexed file to find similar files for
+    pub file_path: String,
+    pub limit: Option<usize>,
+}
+
+impl SimilarDTOInput {
+    pub fn get_limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+    }
+}
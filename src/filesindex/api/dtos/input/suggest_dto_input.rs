@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_SUGGESTIONS: usize = 10;
+pub const MAX_SUGGESTIONS: usize = 100;
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SuggestDTOInput {
+    /// What has been typed so far. Only its last word is completed
+    pub partial: String,
+    pub limit: Option<usize>,
+}
+
+impl SuggestDTOInput {
+    pub fn get_limit(&self) -> usize {
+        self.limit
+            .unwrap_or(DEFAULT_SUGGESTIONS)
+            .clamp(1, MAX_SUGGESTIONS)
+    }
+}
@@ -0,0 +1,29 @@
+use serde::Serialize;
+use tantivy::query::Explanation;
+
+use crate::shared::models::score_weights::ScoreWeights;
+
+use super::file_dto_output::FileDTOOutput;
+
+/**
+ * Breaks down how a file's score came about: the text relevance computed by tantivy, then what the
+ * scorer added on top of it
+ */
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ExplanationDTOOutput {
+    /// Carries the final score, the same one the search would return
+    pub file: FileDTOOutput,
+    pub scorer: String,
+    pub score_weights: ScoreWeights,
+    /// The BM25 score with the field boosts applied, before any scorer
+    pub relevance: f32,
+    /// `log10(popularity)`, before weighting
+    pub popularity: f64,
+    /// Between 0 and 1, before weighting
+    pub recency: f64,
+    /// How much the scorer moved the score away from the relevance
+    pub adjustment: f64,
+    /// How tantivy computed the relevance, clause by clause
+    pub explanation: Explanation,
+}
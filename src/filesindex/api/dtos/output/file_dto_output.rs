@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use crate::shared::dtos::highlight_dto::HighlightDTO;
 use tantivy::DateTime;
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -9,5 +10,10 @@ pub struct FileDTOOutput {
     pub metadata: String,
     pub date_modified: String,
     pub score: f64,
+    pub extension: String,
+    pub category: String,
+    pub size: u64,
+    /// Only filled in when the search asked for highlights
+    pub highlights: Vec<HighlightDTO>,
     pub is_directory:bool,
 }
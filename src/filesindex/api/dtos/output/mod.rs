@@ -1 +1,3 @@
+pub mod explanation_dto_output;
 pub mod file_dto_output;
+pub mod search_results_dto_output;
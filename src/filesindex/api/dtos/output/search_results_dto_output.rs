@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+use crate::shared::dtos::aggregations_dto::AggregationsDTO;
+
+use super::file_dto_output::FileDTOOutput;
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SearchResultsDTOOutput {
+    pub results: Vec<FileDTOOutput>,
+    /// The number of documents matching the query, not just the ones in this page
+    pub total_hits: usize,
+    pub offset: usize,
+    /// Only filled in when the search asked for aggregations
+    pub aggregations: Option<AggregationsDTO>,
+    /// Corrected names to try when nothing matched the name as typed, best first
+    pub did_you_mean: Vec<String>,
+    /// Set when the results are for the best correction rather than the name as typed
+    pub corrected_name: Option<String>,
+}
@@ -12,6 +12,9 @@ pub fn doc_to_dto(doc: TantivyDocument, schema: &Schema, score: f64) -> FileDTOO
     let mut name = String::new();
     let mut file_path = String::new();
     let mut metadata = String::new();
+    let mut extension = String::new();
+    let mut category = String::new();
+    let mut size: u64 = 0;
     let mut date_modified: Option<String> = None;
 
     // Iterate through the document fields and populate the DTO fields
@@ -34,6 +37,21 @@ pub fn doc_to_dto(doc: TantivyDocument, schema: &Schema, score: f64) -> FileDTOO
                     metadata = text.to_string();
                 }
             }
+            "extension" => {
+                if let OwnedValue::Str(text) = value {
+                    extension = text.to_string();
+                }
+            }
+            "category" => {
+                if let OwnedValue::Str(text) = value {
+                    category = text.to_string();
+                }
+            }
+            "size" => {
+                if let OwnedValue::U64(bytes) = value {
+                    size = *bytes;
+                }
+            }
             "date_modified" => {
                 if let OwnedValue::Date(date) = value {
                     date_modified = Some(date.into_utc().to_string());
@@ -50,6 +68,10 @@ pub fn doc_to_dto(doc: TantivyDocument, schema: &Schema, score: f64) -> FileDTOO
         metadata,
         date_modified: date_modified.unwrap_or_else(|| Utc::now().to_string()),
         score,
+        extension,
+        category,
+        size,
+        highlights: Vec::new(),
         is_directory: is_dir,
     }
 }
@@ -1,3 +1,2 @@
 pub mod doc_to_dto;
 pub mod date_converter;
-pub mod path_to_facet;
\ No newline at end of file
@@ -3,4 +3,4 @@ pub mod schemas;
 pub mod models;
 mod queue;
 pub mod converters;
-pub mod scorers;
\ No newline at end of file
+pub mod parsers;
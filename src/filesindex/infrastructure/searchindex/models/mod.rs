@@ -1,2 +1 @@
-pub mod query_result_model;
-pub mod search_params_model;
\ No newline at end of file
+pub mod query_result_model;
\ No newline at end of file
@@ -0,0 +1 @@
+pub mod query_string_parser;
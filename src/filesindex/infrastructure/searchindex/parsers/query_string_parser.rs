@@ -0,0 +1,501 @@
+use std::fmt;
+
+use chrono::{Duration, NaiveDate};
+use serde::Serialize;
+use tantivy::time::OffsetDateTime;
+
+use crate::shared::models::{
+    file_category::FileCategory,
+    search_params_model::{
+        DateRange, DirectoryScope, MatchMode, NamePattern, PatternSyntax, SearchParamsModel,
+        SizeRange,
+    },
+};
+
+const FIELDS: [&str; 10] = [
+    "name", "path", "ext", "type", "size", "modified", "in", "match", "glob", "regex",
+];
+
+/**
+ * A syntax error in a query string. `position` is the 0-based character index where the problem
+ * was found
+ */
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct QuerySyntaxError {
+    pub message: String,
+    pub position: usize,
+}
+
+impl QuerySyntaxError {
+    fn new(message: impl Into<String>, position: usize) -> Self {
+        Self {
+            message: message.into(),
+            position,
+        }
+    }
+}
+
+impl fmt::Display for QuerySyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for QuerySyntaxError {}
+
+struct Token {
+    negated: bool,
+    field: Option<String>,
+    value: String,
+    position: usize,
+    value_position: usize,
+}
+
+#[derive(Clone, Copy)]
+enum Comparison {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+    Equal,
+}
+
+/**
+ * Parses a query string such as `ext:pdf size:>100MB modified:<2024-01-01 in:/home/me/docs
+ * -name:tmp "exact phrase"` into search params.
+ *
+ * Words without a field, and quoted phrases, are matched against the name. A leading `-`
+ * excludes files matching a name, path or extension instead, and `match:all` requires every word
+ * to match. Sizes accept the units B, KB, MB, GB and TB, and dates are written as
+ * `YYYY-MM-DD`. Both can be prefixed with `>`, `>=`, `<`, `<=` or `=`.
+ */
+pub fn parse_query_string(query: &str) -> Result<SearchParamsModel, QuerySyntaxError> {
+    let mut params = SearchParamsModel::default();
+    let mut name_terms: Vec<String> = Vec::new();
+    let mut path_terms: Vec<String> = Vec::new();
+    let mut size_range = SizeRange {
+        min: None,
+        max: None,
+    };
+    let mut date_start: Option<OffsetDateTime> = None;
+    let mut date_end: Option<OffsetDateTime> = None;
+
+    for token in tokenize(query)? {
+        let field = token.field.as_deref().map(str::to_lowercase);
+
+        let negatable = matches!(
+            field.as_deref(),
+            None | Some("name") | Some("path") | Some("ext") | Some("extension")
+        );
+        if token.negated && !negatable {
+            return Err(QuerySyntaxError::new(
+                format!("'{}:' cannot be negated", field.unwrap_or_default()),
+                token.position,
+            ));
+        }
+
+        match field.as_deref() {
+            None | Some("name") => {
+                let term = quote_term(&token.value);
+                if token.negated {
+                    params.exclude_names.get_or_insert_with(Vec::new).push(term);
+                } else {
+                    name_terms.push(term);
+                }
+            }
+            Some("path") => {
+                let term = quote_term(&token.value);
+                if token.negated {
+                    params.exclude_paths.get_or_insert_with(Vec::new).push(term);
+                } else {
+                    path_terms.push(term);
+                }
+            }
+            Some("ext") | Some("extension") => {
+                let extensions = if token.negated {
+                    params.exclude_extensions.get_or_insert_with(Vec::new)
+                } else {
+                    params.extensions.get_or_insert_with(Vec::new)
+                };
+                extensions.extend(
+                    token
+                        .value
+                        .split(',')
+                        .filter(|x| !x.is_empty())
+                        .map(str::to_string),
+                );
+            }
+            Some("type") | Some("category") => {
+                let categories = params.categories.get_or_insert_with(Vec::new);
+                for name in token.value.split(',').filter(|x| !x.is_empty()) {
+                    let category = FileCategory::from_name(name).ok_or_else(|| {
+                        QuerySyntaxError::new(
+                            format!("unknown file type '{}'", name),
+                            token.value_position,
+                        )
+                    })?;
+                    categories.push(category);
+                }
+            }
+            Some("size") => {
+                let (comparison, value) = split_comparison(&token.value);
+                let bytes = parse_size(value).ok_or_else(|| {
+                    QuerySyntaxError::new(
+                        format!("invalid size '{}', expected something like 100MB", value),
+                        token.value_position,
+                    )
+                })?;
+                let (min, max) = match comparison {
+                    Comparison::Greater => (Some(bytes.saturating_add(1)), None),
+                    Comparison::GreaterOrEqual => (Some(bytes), None),
+                    Comparison::Less => match bytes.checked_sub(1) {
+                        Some(max) => (None, Some(max)),
+                        None => {
+                            return Err(QuerySyntaxError::new(
+                                "no file is smaller than 0 bytes",
+                                token.value_position,
+                            ))
+                        }
+                    },
+                    Comparison::LessOrEqual => (None, Some(bytes)),
+                    Comparison::Equal => (Some(bytes), Some(bytes)),
+                };
+                // Repeated bounds narrow the range
+                if let Some(min) = min {
+                    size_range.min = Some(size_range.min.map_or(min, |x| x.max(min)));
+                }
+                if let Some(max) = max {
+                    size_range.max = Some(size_range.max.map_or(max, |x| x.min(max)));
+                }
+            }
+            Some("modified") | Some("date") => {
+                let (comparison, value) = split_comparison(&token.value);
+                let day = NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+                    QuerySyntaxError::new(
+                        format!("invalid date '{}', expected YYYY-MM-DD", value),
+                        token.value_position,
+                    )
+                })?;
+                let out_of_range = || {
+                    QuerySyntaxError::new(
+                        format!("date '{}' is out of range", value),
+                        token.value_position,
+                    )
+                };
+                let day_start = date_to_offset_datetime(day).ok_or_else(out_of_range)?;
+                let next_day_start = day
+                    .checked_add_signed(Duration::days(1))
+                    .and_then(date_to_offset_datetime)
+                    .ok_or_else(out_of_range)?;
+                // The end of the date range is exclusive
+                let (start, end) = match comparison {
+                    Comparison::Greater => (Some(next_day_start), None),
+                    Comparison::GreaterOrEqual => (Some(day_start), None),
+                    Comparison::Less => (None, Some(day_start)),
+                    Comparison::LessOrEqual => (None, Some(next_day_start)),
+                    Comparison::Equal => (Some(day_start), Some(next_day_start)),
+                };
+                if let Some(start) = start {
+                    date_start = Some(date_start.map_or(start, |x| x.max(start)));
+                }
+                if let Some(end) = end {
+                    date_end = Some(date_end.map_or(end, |x| x.min(end)));
+                }
+            }
+            Some("in") | Some("dir") => {
+                if params.directory.is_some() {
+                    return Err(QuerySyntaxError::new(
+                        "only one 'in:' directory can be given",
+                        token.position,
+                    ));
+                }
+                params.directory = Some(DirectoryScope {
+                    path: token.value,
+                    recursive: true,
+                });
+            }
+            Some("glob") | Some("regex") => {
+                if params.name_pattern.is_some() {
+                    return Err(QuerySyntaxError::new(
+                        "only one 'glob:' or 'regex:' pattern can be given",
+                        token.position,
+                    ));
+                }
+                let syntax = if field.as_deref() == Some("glob") {
+                    PatternSyntax::Glob
+                } else {
+                    PatternSyntax::Regex
+                };
+                params.name_pattern = Some(NamePattern {
+                    pattern: token.value,
+                    syntax,
+                });
+            }
+            Some("match") => {
+                params.match_mode = match token.value.to_lowercase().as_str() {
+                    "all" => MatchMode::All,
+                    "any" => MatchMode::Any,
+                    _ => {
+                        return Err(QuerySyntaxError::new(
+                            format!("invalid match mode '{}', expected all or any", token.value),
+                            token.value_position,
+                        ))
+                    }
+                };
+            }
+            Some(other) => {
+                return Err(QuerySyntaxError::new(
+                    format!(
+                        "unknown field '{}', expected one of {}",
+                        other,
+                        FIELDS.join(", ")
+                    ),
+                    token.position,
+                ))
+            }
+        }
+    }
+
+    if !name_terms.is_empty() {
+        params.name = Some(name_terms.join(" "));
+    }
+    if !path_terms.is_empty() {
+        params.file_path = Some(path_terms.join(" "));
+    }
+    if size_range.min.is_some() || size_range.max.is_some() {
+        params.size_range = Some(size_range);
+    }
+    if date_start.is_some() || date_end.is_some() {
+        params.date_range = Some(DateRange {
+            start: date_start.unwrap_or(OffsetDateTime::UNIX_EPOCH),
+            end: date_end.unwrap_or_else(latest_indexable_date),
+        });
+    }
+
+    Ok(params)
+}
+
+fn tokenize(query: &str) -> Result<Vec<Token>, QuerySyntaxError> {
+    let chars: Vec<char> = query.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        if chars[i].is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let position = i;
+        let negated = chars[i] == '-' && chars.get(i + 1).is_some_and(|c| !c.is_whitespace());
+        if negated {
+            i += 1;
+        }
+
+        // A phrase on its own is matched against the name
+        if chars[i] == '"' {
+            let (value, end) = read_quoted(&chars, i)?;
+            tokens.push(Token {
+                negated,
+                field: None,
+                value,
+                position,
+                value_position: i,
+            });
+            i = end;
+            continue;
+        }
+
+        let word_start = i;
+        while i < chars.len() && !chars[i].is_whitespace() && chars[i] != ':' {
+            if chars[i] == '"' {
+                return Err(QuerySyntaxError::new("unexpected quote", i));
+            }
+            i += 1;
+        }
+        let word: String = chars[word_start..i].iter().collect();
+
+        if i >= chars.len() || chars[i] != ':' {
+            tokens.push(Token {
+                negated,
+                field: None,
+                value: word,
+                position,
+                value_position: word_start,
+            });
+            continue;
+        }
+
+        // Skip the colon, the rest of the word is the value and may itself contain colons
+        i += 1;
+        let value_position = i;
+        let value = if chars.get(i) == Some(&'"') {
+            let (value, end) = read_quoted(&chars, i)?;
+            i = end;
+            value
+        } else {
+            while i < chars.len() && !chars[i].is_whitespace() {
+                if chars[i] == '"' {
+                    return Err(QuerySyntaxError::new("unexpected quote", i));
+                }
+                i += 1;
+            }
+            chars[value_position..i].iter().collect()
+        };
+
+        if value.is_empty() {
+            return Err(QuerySyntaxError::new(
+                format!("expected a value after '{}:'", word),
+                value_position,
+            ));
+        }
+
+        tokens.push(Token {
+            negated,
+            field: Some(word),
+            value,
+            position,
+            value_position,
+        });
+    }
+
+    Ok(tokens)
+}
+
+/**
+ * Reads the phrase that opens at `start`, returning it along with the index just past the
+ * closing quote
+ */
+fn read_quoted(chars: &[char], start: usize) -> Result<(String, usize), QuerySyntaxError> {
+    let end = chars[start + 1..]
+        .iter()
+        .position(|c| *c == '"')
+        .map(|x| start + 1 + x)
+        .ok_or_else(|| QuerySyntaxError::new("unterminated quote", start))?;
+    Ok((chars[start + 1..end].iter().collect(), end + 1))
+}
+
+/**
+ * Quoting every term keeps characters such as parentheses from being read as tantivy query
+ * syntax, and makes words split by the filename tokenizer match as a phrase. Backslashes and
+ * quotes are escaped so that they can't end the phrase early, e.g. in `C:\Users\me\`
+ */
+pub fn quote_term(term: &str) -> String {
+    format!("\"{}\"", term.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn split_comparison(value: &str) -> (Comparison, &str) {
+    if let Some(rest) = value.strip_prefix(">=") {
+        (Comparison::GreaterOrEqual, rest)
+    } else if let Some(rest) = value.strip_prefix("<=") {
+        (Comparison::LessOrEqual, rest)
+    } else if let Some(rest) = value.strip_prefix('>') {
+        (Comparison::Greater, rest)
+    } else if let Some(rest) = value.strip_prefix('<') {
+        (Comparison::Less, rest)
+    } else {
+        (Comparison::Equal, value.strip_prefix('=').unwrap_or(value))
+    }
+}
+
+/**
+ * Parses sizes like `512`, `1.5GB` or `100mb`, using powers of 1024
+ */
+fn parse_size(value: &str) -> Option<u64> {
+    let unit_start = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(unit_start);
+    let number: f64 = number.parse().ok()?;
+    let multiplier: u64 = match unit.to_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1 << 10,
+        "m" | "mb" => 1 << 20,
+        "g" | "gb" => 1 << 30,
+        "t" | "tb" => 1 << 40,
+        _ => return None,
+    };
+    Some((number * multiplier as f64) as u64)
+}
+
+/**
+ * `None` if tantivy can't represent the start of the day, see `latest_indexable_date`
+ */
+fn date_to_offset_datetime(date: NaiveDate) -> Option<OffsetDateTime> {
+    let timestamp = date.and_hms_opt(0, 0, 0)?.and_utc().timestamp();
+    if timestamp.unsigned_abs() > (i64::MAX / 1_000_000_000) as u64 {
+        return None;
+    }
+    OffsetDateTime::from_unix_timestamp(timestamp).ok()
+}
+
+/**
+ * Tantivy stores dates as nanoseconds in an i64, so later dates can't be represented
+ */
+fn latest_indexable_date() -> OffsetDateTime {
+    OffsetDateTime::from_unix_timestamp(i64::MAX / 1_000_000_000).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::{
+        query::QueryParser,
+        schema::{Schema, TEXT},
+        Index,
+    };
+
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> OffsetDateTime {
+        date_to_offset_datetime(NaiveDate::from_ymd_opt(year, month, day).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn parses_every_field_of_the_example_query() {
+        let params = parse_query_string(
+            r#"ext:pdf size:>100MB modified:<2024-01-01 in:/home/me/docs -name:tmp "exact phrase""#,
+        )
+        .unwrap();
+
+        assert_eq!(params.name.as_deref(), Some(r#""exact phrase""#));
+        assert_eq!(params.exclude_names, Some(vec![r#""tmp""#.to_string()]));
+        assert_eq!(params.extensions, Some(vec!["pdf".to_string()]));
+
+        let size_range = params.size_range.unwrap();
+        assert_eq!(size_range.min, Some(100 * (1 << 20) + 1));
+        assert_eq!(size_range.max, None);
+
+        let date_range = params.date_range.unwrap();
+        assert_eq!(date_range.start, OffsetDateTime::UNIX_EPOCH);
+        assert_eq!(date_range.end, date(2024, 1, 1));
+
+        let directory = params.directory.unwrap();
+        assert_eq!(directory.path, "/home/me/docs");
+        assert!(directory.recursive);
+
+        assert!(params.file_path.is_none());
+        assert_eq!(params.match_mode, MatchMode::Any);
+    }
+
+    #[test]
+    fn dates_tantivy_cannot_store_are_syntax_errors() {
+        let query = "modified:<+12345-01-01";
+        let err = parse_query_string(query).unwrap_err();
+        assert_eq!(err.position, query.find('<').unwrap());
+    }
+
+    #[test]
+    fn quoted_terms_escape_backslashes_and_quotes() {
+        let params = parse_query_string(r"path:C:\Users\me\").unwrap();
+        assert_eq!(params.file_path.as_deref(), Some(r#""C:\\Users\\me\\""#));
+        assert_eq!(quote_term(r#"a "b""#), r#""a \"b\"""#);
+
+        // The escaped phrase has to end where the quoted term does
+        let mut schema_builder = Schema::builder();
+        let path_field = schema_builder.add_text_field("path", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let query_parser = QueryParser::for_index(&index, vec![path_field]);
+        assert!(query_parser.parse_query(&params.file_path.unwrap()).is_ok());
+    }
+}
@@ -1,14 +1,18 @@
-use crate::filesindex::{
-    api::dtos::input::file_dto_input::FileDTOInput,
-    infrastructure::searchindex::converters::{
-        date_converter::unix_time_to_tantivy_datetime, path_to_facet::windows_path_to_facet,
+use crate::{
+    filesindex::{
+        api::dtos::input::file_dto_input::FileDTOInput,
+        infrastructure::searchindex::converters::date_converter::unix_time_to_tantivy_datetime,
+    },
+    shared::{
+        index_maintenance::{derived_fields::add_derived_fields, file_key::file_key_term},
+        models::file_category::{file_extension, FileCategory},
     },
 };
 
 use std::{path::Path, sync::Arc, time::Duration};
 use tantivy::{
     doc,
-    schema::{Facet, Schema},
+    schema::Schema,
     Document, IndexWriter, TantivyDocument, TantivyError,
 };
 use tokio::{
@@ -40,17 +44,26 @@ pub async fn index_worker(
             {
                 let writer = index_writer.lock().await;
 
-                //let formatted_facet_path = windows_path_to_facet(&dto.file_path);
+                let extension = file_extension(Path::new(&dto.file_path));
+                let category = FileCategory::from_extension(&extension);
+                let name = dto.name.clone();
+                let file_path = dto.file_path.clone();
 
-                writer
-                .add_document(doc!(
+                let mut document = doc!(
                     schema.get_field("file_id").unwrap() => dto.file_id,
                     schema.get_field("name").unwrap() => dto.name,
                     schema.get_field("date_modified").unwrap() => unix_time_to_tantivy_datetime(dto.date_modified),
                     schema.get_field("path").unwrap() => dto.file_path,
                     schema.get_field("metadata").unwrap() => dto.metadata,
                     schema.get_field("popularity").unwrap() => dto.popularity,
-                ))
+                    schema.get_field("extension").unwrap() => extension,
+                    schema.get_field("category").unwrap() => category.as_str(),
+                    schema.get_field("size").unwrap() => dto.size,
+                );
+                add_derived_fields(&mut document, &schema, &name, &file_path);
+
+                writer
+                .add_document(document)
                 .unwrap(); // Consider proper error handling here
 
                 batch_on += 1;
@@ -71,16 +84,6 @@ pub async fn index_worker(
     }
 }
 
-fn build_facet_from_file_path(path: &str) -> Facet {
-    let path = Path::new(path);
-    let mut facet_path = String::from("/");
-    for component in path.components() {
-        facet_path.push_str(&component.as_os_str().to_string_lossy());
-        facet_path.push('/');
-    }
-    Facet::from(&facet_path)
-}
-
 async fn commit_and_retry(writer: Arc<Mutex<IndexWriter>>) -> Result<(), TantivyError> {
     let retry_attempts = 3;
     for attempt in 1..=retry_attempts {
@@ -106,11 +109,64 @@ async fn remove_file_from_index(
     file_path: &str,
 ) -> tantivy::Result<()> {
     let index_writer = index_writer.lock().await;
-    match schema.get_field("file_id") {
-        Ok(field) => {
-            index_writer.delete_term(tantivy::Term::from_field_text(field, file_path));
+    match file_key_term(schema, file_path) {
+        Ok(term) => {
+            index_writer.delete_term(term);
             Ok(())
         }
         Err(e) => Err(e),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tantivy::{
+        collector::TopDocs,
+        query::TermQuery,
+        schema::{IndexRecordOption, Value},
+        Index,
+    };
+
+    use super::*;
+    use crate::{
+        filesindex::infrastructure::searchindex::schemas::file_schema::create_schema,
+        shared::tokenizers::filename_tokenizer::register_filename_tokenizer,
+    };
+
+    fn file_dto(file_path: &str, size: u64) -> FileDTOInput {
+        FileDTOInput {
+            file_id: file_path.to_string(),
+            name: "notes.txt".to_string(),
+            file_path: file_path.to_string(),
+            metadata: String::new(),
+            date_modified: 0,
+            popularity: 1.0,
+            size,
+        }
+    }
+
+    #[tokio::test]
+    async fn indexing_a_path_again_replaces_its_document() {
+        let schema = create_schema();
+        let index = Index::create_in_ram(schema.clone());
+        register_filename_tokenizer(&index);
+        let writer = Arc::new(Mutex::new(index.writer(15_000_000).unwrap()));
+
+        let (sender, receiver) = mpsc::channel(8);
+        sender.send(file_dto("/home/me/notes.txt", 10)).await.unwrap();
+        // The same file, written with a trailing separator
+        sender.send(file_dto("/home/me/notes.txt/", 20)).await.unwrap();
+        drop(sender);
+        index_worker(receiver, writer, schema.clone(), 1).await;
+
+        let searcher = index.reader().unwrap().searcher();
+        let term = file_key_term(&schema, "/home/me/notes.txt").unwrap();
+        let query = TermQuery::new(term, IndexRecordOption::Basic);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(10)).unwrap();
+        assert_eq!(top_docs.len(), 1);
+        assert_eq!(searcher.num_docs(), 1);
+        let doc: TantivyDocument = searcher.doc(top_docs[0].1).unwrap();
+        let size_field = schema.get_field("size").unwrap();
+        assert_eq!(doc.get_first(size_field).and_then(|x| x.as_u64()), Some(20));
+    }
+}
@@ -1 +0,0 @@
-pub mod pop_scorer;
\ No newline at end of file
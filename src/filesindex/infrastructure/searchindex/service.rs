@@ -1,27 +1,55 @@
-use std::{fs, future::Future, path::PathBuf, sync::Arc};
+use std::{
+    fs,
+    future::Future,
+    sync::{Arc, RwLock},
+};
 use tantivy::{
-    collector::TopDocs,
-    query::{BooleanQuery, FuzzyTermQuery, Occur, Query, QueryParser, RangeQuery, TermQuery},
-    schema::{Facet, Schema, Value},
-    DateTime, DocId, Index, IndexReader, IndexWriter, Score, SegmentReader, TantivyDocument, Term,
+    query::Query,
+    schema::{Schema, Value},
+    time::OffsetDateTime,
+    DateTime, Index, IndexReader, IndexWriter, Searcher, TantivyDocument, TantivyError,
 };
 use tokio::sync::{mpsc, Mutex};
 
-use crate::filesindex::{
-    api::dtos::{input::file_dto_input::FileDTOInput, output::file_dto_output::FileDTOOutput},
-    file_indexer_config::FileIndexerConfig,
+use crate::{
+    filesindex::{
+        api::dtos::{
+            input::file_dto_input::FileDTOInput,
+            output::{
+                explanation_dto_output::ExplanationDTOOutput, file_dto_output::FileDTOOutput,
+                search_results_dto_output::SearchResultsDTOOutput,
+            },
+        },
+        file_indexer_config::FileIndexerConfig,
+    },
+    shared::{
+        index_maintenance::{index_rebuilder, reindexer},
+        models::search_params_model::SearchParamsModel,
+        queries::{
+            file_lookup::find_file,
+            file_query::{
+                build_file_query, corrected_params, did_you_mean, file_scorer, highlights_for_doc,
+                search_files, snippet_generators,
+            },
+            similar_files::similar_files,
+        },
+        scorers::{
+            file_scorer::DEFAULT_SCORER, pop_scorer::popularity_score,
+            recency_scorer::recency_score,
+        },
+        tokenizers::filename_tokenizer::register_filename_tokenizer,
+    },
 };
 
 use super::{
-    converters::doc_to_dto::doc_to_dto, models::search_params_model::SearchParamsModel,
-    queue::index_worker, schemas::file_schema::create_schema,
-    scorers::pop_scorer::apply_popularity,
+    converters::doc_to_dto::doc_to_dto, queue::index_worker, schemas::file_schema::create_schema,
 };
 
 pub struct SearchIndexService {
     schema: Schema,
     index_writer: Arc<Mutex<IndexWriter>>,
-    index_reader: IndexReader,
+    // Swapped for the reader of the new index once a rebuild is done
+    index_reader: Arc<RwLock<IndexReader>>,
     config: FileIndexerConfig,
 }
 
@@ -29,96 +57,175 @@ impl SearchIndexService {
     pub fn new(config: &FileIndexerConfig) -> Self {
         let schema = create_schema();
         let index_path = config.tantivy_out_path.clone();
+        reindexer::finish_pending_rebuild(&index_path).expect("could not finish rebuilding index");
 
         let index = if index_path.exists() {
             // If the index directory exists, open the existing index
             println!("Opening existing index at {:?}", index_path);
-            Index::open_in_dir(index_path)
+            Index::open_in_dir(&index_path)
         } else {
             // If the index directory doesn't exist, create a new index
             println!("Creating a new index at {:?}", index_path);
             fs::create_dir_all(index_path.clone()).expect("could not create output directory");
-            Index::create_in_dir(index_path, schema.clone())
+            Index::create_in_dir(&index_path, schema.clone())
         };
         let index = index.unwrap();
-        let index_writer = index.writer(config.buffer_size).unwrap();
-
-        let writer_clone = Arc::new(Mutex::new(index_writer));
-        let index_reader = index.reader().unwrap();
+        register_filename_tokenizer(&index);
+        let index_reader = Arc::new(RwLock::new(index.reader().unwrap()));
+
+        // Files are sent to this service rather than crawled, so only their senders can fill these in
+        let uncopied_fields = reindexer::uncopied_fields(&index.schema(), &schema);
+        if !uncopied_fields.is_empty() {
+            println!(
+                "Files have to be indexed again to fill in fields the old index doesn't have: {}",
+                uncopied_fields.join(", ")
+            );
+        }
+        let index_writer = index_rebuilder::open_writer(
+            index,
+            &index_path,
+            &schema,
+            config.buffer_size,
+            index_reader.clone(),
+        )
+        .expect("could not open index writer");
 
         Self {
             config: config.clone(),
             schema,
-            index_writer: writer_clone,
+            index_writer,
             index_reader,
         }
     }
 
+    /**
+     * Lets searches that don't need the rest of the service run without locking it. The reader is
+     * swapped for the new index's once a rebuild completes, so take a searcher from it each time
+     * instead of keeping a clone
+     */
+    pub fn shared_reader(&self) -> Arc<RwLock<IndexReader>> {
+        self.index_reader.clone()
+    }
+
+    fn searcher(&self) -> Searcher {
+        self.index_reader.read().unwrap().searcher()
+    }
+
     pub fn advanced_query(
         &self,
         search_params: &SearchParamsModel, // Struct holding the user's search criteria
-    ) -> tantivy::Result<Vec<FileDTOOutput>> {
-        let schema = &self.schema;
-        let searcher = self.index_reader.searcher();
+    ) -> tantivy::Result<SearchResultsDTOOutput> {
+        let searcher = self.searcher();
+        let schema = searcher.schema();
+        let default_weights = &self.config.score_weights;
 
-        let mut queries: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+        let boolean_query = build_file_query(&searcher, search_params, &self.config.field_boosts)?;
+        let hits = search_files(&searcher, &boolean_query, search_params, default_weights)?;
+        let snippet_generators = snippet_generators(&searcher, &boolean_query, search_params)?;
 
-        if let Some(file_path) = &search_params.file_path {
-            let field = schema.get_field("path").unwrap();
-            let query_parser = QueryParser::for_index(searcher.index(), vec![field]);
-            let query = query_parser.parse_query(file_path)?;
-            queries.push((Occur::Should, Box::new(query)));
-        }
+        let results: Vec<FileDTOOutput> = hits
+            .docs
+            .into_iter()
+            .map(|(_score, doc_address)| {
+                let doc: TantivyDocument = searcher.doc(doc_address).unwrap();
+                let highlights = highlights_for_doc(&snippet_generators, &doc);
+                let mut dto = doc_to_dto(doc, schema, _score);
+                dto.highlights = highlights;
+                dto
+            })
+            .collect();
 
-        if let Some(query_str) = &search_params.name {
-            let field = schema.get_field("name").unwrap();
-            let query_parser = QueryParser::for_index(searcher.index(), vec![field]);
-            let query = query_parser.parse_query(query_str)?;
-            queries.push((Occur::Should, Box::new(query)));
+        let did_you_mean = did_you_mean(&searcher, search_params, hits.total_hits)?;
+        if let Some((correction, corrected_params)) = corrected_params(search_params, &did_you_mean)
+        {
+            let mut corrected_results = self.advanced_query(&corrected_params)?;
+            corrected_results.corrected_name = Some(correction);
+            corrected_results.did_you_mean = did_you_mean;
+            return Ok(corrected_results);
         }
 
-        if let Some(date_range) = &search_params.date_range {
-            let start_date = DateTime::from_utc(date_range.start);
-            let end_date = DateTime::from_utc(date_range.end);
-            let query = RangeQuery::new_date("date_modified".to_string(), start_date..end_date);
-            queries.push((Occur::Must, Box::new(query)));
-        }
+        Ok(SearchResultsDTOOutput {
+            results,
+            total_hits: hits.total_hits,
+            offset: search_params.get_offset(),
+            aggregations: hits.aggregations,
+            did_you_mean,
+            corrected_name: None,
+        })
+    }
 
-        if let Some(metadata) = &search_params.metadata {
-            let field = schema.get_field("metadata").unwrap();
-            let term = Term::from_field_text(field, metadata);
-            let query = TermQuery::new(term, tantivy::schema::IndexRecordOption::Basic);
-            queries.push((Occur::Must, Box::new(query)));
-        }
+    /**
+     * Explains how the file at `file_path` ranks for `search_params`. Fails if the file isn't
+     * indexed or doesn't match the search
+     */
+    pub fn explain(
+        &self,
+        search_params: &SearchParamsModel,
+        file_path: &str,
+    ) -> tantivy::Result<ExplanationDTOOutput> {
+        let searcher = self.searcher();
+        let schema = searcher.schema();
+
+        let doc_address = find_file(&searcher, file_path)?.ok_or_else(|| {
+            TantivyError::InvalidArgument(format!("{} is not indexed", file_path))
+        })?;
+        let boolean_query = build_file_query(&searcher, search_params, &self.config.field_boosts)?;
+        let explanation = boolean_query.explain(&searcher, doc_address)?;
+        let relevance = explanation.value();
+
+        let weights = search_params
+            .score_weights
+            .unwrap_or(self.config.score_weights);
+        let scorer_name = search_params.scorer.as_deref().unwrap_or(DEFAULT_SCORER);
+        let scorer = file_scorer(Some(scorer_name), weights)?;
+        let segment_reader = searcher.segment_reader(doc_address.segment_ord);
+        let score = scorer.for_segment(segment_reader.fast_fields())(doc_address.doc_id, relevance);
+
+        let doc: TantivyDocument = searcher.doc(doc_address)?;
+        let popularity = doc
+            .get_first(schema.get_field("popularity")?)
+            .and_then(|x| x.as_f64())
+            .unwrap_or(1.0);
+        let now = DateTime::from_utc(OffsetDateTime::now_utc());
+        let recency = doc
+            .get_first(schema.get_field("date_modified")?)
+            .and_then(|x| x.as_datetime())
+            .map_or(0.0, |date_modified| {
+                recency_score(date_modified, now, weights.recency_half_life_days)
+            });
+
+        Ok(ExplanationDTOOutput {
+            file: doc_to_dto(doc, schema, score),
+            scorer: scorer_name.to_lowercase(),
+            score_weights: weights,
+            relevance,
+            popularity: popularity_score(popularity),
+            recency,
+            adjustment: score - relevance as f64,
+            explanation,
+        })
+    }
 
-        // Combine all the queries into a BooleanQuery
-        let boolean_query = BooleanQuery::new(queries);
-
-        // Execute the query and collect the results
-        let top_docs = searcher.search(
-            &boolean_query,
-            &TopDocs::with_limit(10).tweak_score(|segment_reader: &SegmentReader| {
-                let popularity_field = segment_reader
-                    .fast_fields()
-                    .f64("popularity")
-                    .expect("Failed to access popularity field");
-                move |doc: DocId, original_score: Score| {
-                    // Default to 1 if no popularity
-                    let pop_score = popularity_field.first(doc).unwrap_or(1.0);
-                    apply_popularity(original_score, pop_score)
-                }
-            }),
-        )?;
-
-        let results: Vec<FileDTOOutput> = top_docs
+    /**
+     * Files whose name, path and metadata resemble those of the file at `file_path`, most similar
+     * first
+     */
+    pub fn similar_files(
+        &self,
+        file_path: &str,
+        limit: usize,
+    ) -> tantivy::Result<Vec<FileDTOOutput>> {
+        let searcher = self.searcher();
+        let doc_address = find_file(&searcher, file_path)?.ok_or_else(|| {
+            TantivyError::InvalidArgument(format!("{} is not indexed", file_path))
+        })?;
+        similar_files(&searcher, doc_address, &self.config.field_boosts, limit)?
             .into_iter()
-            .map(|(_score, doc_address)| {
-                let doc: TantivyDocument = searcher.doc(doc_address).unwrap();
-                doc_to_dto(doc, schema, _score)
+            .map(|(score, doc_address)| {
+                let doc: TantivyDocument = searcher.doc(doc_address)?;
+                Ok(doc_to_dto(doc, searcher.schema(), score as f64))
             })
-            .collect();
-
-        Ok(results)
+            .collect()
     }
 
     pub fn set_up_queue_pipeline(&self) -> mpsc::Sender<FileDTOInput> {
@@ -4,23 +4,49 @@ use actix_web::{http, App, HttpServer};
 use filesindex::api::controller::FilesIndexController;
 use filesindex::file_indexer_config::FileIndexerConfig;
 use filesindex::infrastructure::searchindex::service::SearchIndexService;
+use tantivy_file_indexer::api::controller::SearchController;
 use tantivy_file_indexer::service_container::AppServiceContainer;
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
 mod filesindex;
 mod tantivy_file_indexer;
 mod shared;
 
+const SERVER_ADDRESS: (&str, u16) = ("127.0.0.1", 8080);
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
 
-    initialize_services().await;
+    let service_container = initialize_services().await;
+
+    let controller = Arc::new(SearchController::new(
+        service_container.search_service.clone(),
+        service_container.sqlx_service.clone(),
+    ));
+    // Ctrl+C is handled below, so that the services shut down after the server
+    let server = HttpServer::new(move || {
+        let controller = Arc::clone(&controller);
+        App::new()
+            .wrap(Cors::permissive())
+            .configure(|cfg| controller.map_routes(cfg))
+    })
+    .disable_signals()
+    .bind(SERVER_ADDRESS)?
+    .run();
+    let server_handle = server.handle();
+    actix_web::rt::spawn(server);
+
+    signal::ctrl_c().await?;
+    println!("Shutting down");
+    server_handle.stop(true).await;
+    service_container.shutdown().await;
 
     Ok(())
 }
 
-async fn initialize_services() {
+async fn initialize_services() -> AppServiceContainer {
 
     let index_files = true;
 
@@ -31,10 +57,14 @@ async fn initialize_services() {
     if index_files {
         let sender = service_container
             .search_service
-            .spawn_indexer(db_service, 128, 4);
+            .spawn_indexer(db_service.clone(), 128, 4, Duration::from_secs(30));
+        service_container
+            .search_service
+            .spawn_popularity_updater(db_service, Duration::from_secs(300));
 
         crawler_service.spawn_crawler(sender);
         crawler_service.load_or(vec!["C:\\"]).await;
     }
 
+    service_container
 }
\ No newline at end of file
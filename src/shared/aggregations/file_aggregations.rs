@@ -0,0 +1,145 @@
+use chrono::{Datelike, NaiveDate, Utc};
+use serde_json::{json, Value};
+use tantivy::{
+    aggregation::{
+        agg_req::Aggregations,
+        agg_result::{AggregationResult, AggregationResults, BucketEntries, BucketResult},
+        AggregationCollector, AggregationLimits, Key,
+    },
+    collector::{FacetCollector, FacetCounts},
+};
+
+use crate::shared::dtos::aggregations_dto::{AggregationsDTO, BucketDTO};
+
+// The most buckets returned for extensions and directories
+const BUCKET_LIMIT: usize = 20;
+const FIRST_YEAR: i32 = 1970;
+
+// Upper bounds of the size buckets in bytes, the last bucket holds everything larger
+const SIZE_BUCKETS: [(&str, u64); 5] = [
+    ("< 100 KB", 100 << 10),
+    ("100 KB - 1 MB", 1 << 20),
+    ("1 MB - 10 MB", 10 << 20),
+    ("10 MB - 100 MB", 100 << 20),
+    ("100 MB - 1 GB", 1 << 30),
+];
+const LARGEST_SIZE_BUCKET: &str = "> 1 GB";
+
+pub type FileAggregationsCollector = (AggregationCollector, FacetCollector);
+
+/**
+ * Counts the matching files per extension, top-level directory, modified year and size bucket.
+ * Meant to be combined with the `TopDocs` collector so that it runs in the same pass
+ */
+pub fn file_aggregations_collector() -> FileAggregationsCollector {
+    let aggregations: Aggregations = serde_json::from_value(json!({
+        "extensions": {
+            "terms": { "field": "extension", "size": BUCKET_LIMIT }
+        },
+        "years": {
+            "range": { "field": "date_modified", "ranges": year_ranges() }
+        },
+        "sizes": {
+            "range": { "field": "size", "ranges": size_ranges() }
+        },
+    }))
+    .expect("Failed to build aggregations");
+
+    let mut facet_collector = FacetCollector::for_field("directory");
+    facet_collector.add_facet("/");
+
+    (
+        AggregationCollector::from_aggs(aggregations, AggregationLimits::default()),
+        facet_collector,
+    )
+}
+
+pub fn aggregations_to_dto(
+    (aggregation_results, facet_counts): (AggregationResults, FacetCounts),
+) -> AggregationsDTO {
+    let directories = facet_counts
+        .top_k("/", BUCKET_LIMIT)
+        .into_iter()
+        .map(|(facet, count)| BucketDTO {
+            key: facet.to_path_string(),
+            count,
+        })
+        .collect();
+
+    AggregationsDTO {
+        extensions: buckets(&aggregation_results, "extensions"),
+        directories,
+        years: buckets(&aggregation_results, "years"),
+        sizes: buckets(&aggregation_results, "sizes"),
+    }
+}
+
+/**
+ * One range per year, keyed by the year. Date fast fields are compared in nanoseconds
+ */
+fn year_ranges() -> Vec<Value> {
+    let current_year = Utc::now().year();
+    (FIRST_YEAR..=current_year)
+        .map(|year| {
+            // Open ended at both sides so that no file is left without a bucket
+            let from = (year != FIRST_YEAR).then(|| year_start_nanos(year));
+            let to = (year != current_year).then(|| year_start_nanos(year + 1));
+            json!({ "key": year.to_string(), "from": from, "to": to })
+        })
+        .collect()
+}
+
+fn year_start_nanos(year: i32) -> i64 {
+    let start = NaiveDate::from_ymd_opt(year, 1, 1).unwrap();
+    start.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp() * 1_000_000_000
+}
+
+fn size_ranges() -> Vec<Value> {
+    let mut from = None;
+    let mut ranges: Vec<Value> = SIZE_BUCKETS
+        .iter()
+        .map(|(key, to)| {
+            let range = json!({ "key": key, "from": from, "to": to });
+            from = Some(*to);
+            range
+        })
+        .collect();
+    ranges.push(json!({ "key": LARGEST_SIZE_BUCKET, "from": from }));
+    ranges
+}
+
+// Empty buckets are left out
+fn buckets(results: &AggregationResults, name: &str) -> Vec<BucketDTO> {
+    let bucket_result = match results.0.get(name) {
+        Some(AggregationResult::BucketResult(bucket_result)) => bucket_result,
+        _ => return Vec::new(),
+    };
+    let buckets: Vec<(&Key, u64)> = match bucket_result {
+        BucketResult::Terms { buckets, .. } => buckets
+            .iter()
+            .map(|bucket| (&bucket.key, bucket.doc_count))
+            .collect(),
+        BucketResult::Range { buckets } => match buckets {
+            BucketEntries::Vec(buckets) => buckets
+                .iter()
+                .map(|bucket| (&bucket.key, bucket.doc_count))
+                .collect(),
+            BucketEntries::HashMap(buckets) => buckets
+                .values()
+                .map(|bucket| (&bucket.key, bucket.doc_count))
+                .collect(),
+        },
+        _ => Vec::new(),
+    };
+    buckets
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(key, count)| BucketDTO {
+            key: match key {
+                Key::Str(key) => key.clone(),
+                Key::F64(key) => key.to_string(),
+            },
+            count,
+        })
+        .collect()
+}
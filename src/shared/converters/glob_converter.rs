@@ -0,0 +1,100 @@
+/**
+ * Converts a glob such as `*.log.[0-9]` into an equivalent regex. `*` matches any run of
+ * characters, `?` a single character and `[...]` (or `[!...]` for negation) a character class.
+ * A `[` that is never closed is matched literally. Letters are lowercased to match the lowercase
+ * copy of the name
+ */
+pub fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::with_capacity(glob.len() * 2);
+    let mut in_class = false;
+    let mut chars = glob.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_class {
+            if c == ']' {
+                in_class = false;
+            } else if c == '\\' || c == '[' {
+                regex.push('\\');
+            }
+            regex.extend(c.to_lowercase());
+            continue;
+        }
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            '[' if !chars.clone().any(|next| next == ']') => regex.push_str("\\["),
+            '[' => {
+                in_class = true;
+                regex.push('[');
+                if chars.peek() == Some(&'!') {
+                    chars.next();
+                    regex.push('^');
+                }
+            }
+            _ => {
+                if is_regex_meta(c) {
+                    regex.push('\\');
+                }
+                regex.extend(c.to_lowercase());
+            }
+        }
+    }
+    regex
+}
+
+fn is_regex_meta(c: char) -> bool {
+    matches!(
+        c,
+        '\\' | '.'
+            | '+'
+            | '*'
+            | '?'
+            | '('
+            | ')'
+            | '|'
+            | '['
+            | ']'
+            | '{'
+            | '}'
+            | '^'
+            | '$'
+            | '#'
+            | '&'
+            | '-'
+            | '~'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::{query::RegexQuery, schema::Field};
+
+    use super::*;
+
+    fn is_valid_regex(regex: &str) -> bool {
+        RegexQuery::from_pattern(regex, Field::from_field_id(0)).is_ok()
+    }
+
+    #[test]
+    fn wildcards_become_regex_and_the_rest_is_escaped() {
+        assert_eq!(glob_to_regex("*.log"), ".*\\.log");
+        assert_eq!(glob_to_regex("Report?.PDF"), "report.\\.pdf");
+        assert_eq!(glob_to_regex("file (1)+$.txt"), "file \\(1\\)\\+\\$\\.txt");
+        assert!(is_valid_regex(&glob_to_regex("a{1}|b^c~d&e-f#g")));
+    }
+
+    #[test]
+    fn character_classes_are_kept_and_can_be_negated() {
+        assert_eq!(glob_to_regex("*.log.[0-9]"), ".*\\.log\\.[0-9]");
+        assert_eq!(glob_to_regex("[!A-C]*"), "[^a-c].*");
+        assert_eq!(glob_to_regex("[[]x]"), "[\\[]x\\]");
+        assert!(is_valid_regex(&glob_to_regex("[[]x]")));
+    }
+
+    #[test]
+    fn an_unclosed_bracket_is_literal() {
+        assert_eq!(glob_to_regex("a[b"), "a\\[b");
+        assert_eq!(glob_to_regex("[!"), "\\[!");
+        assert!(is_valid_regex(&glob_to_regex("a[b*")));
+    }
+}
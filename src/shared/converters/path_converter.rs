@@ -0,0 +1,31 @@
+use tantivy::schema::Facet;
+
+/**
+ * Splits on both Windows and Unix separators since the index can hold paths from either, e.g.
+ * `C:\Users\me\notes.txt` becomes `C:`, `Users`, `me`, `notes.txt`
+ */
+fn path_components(path: &str) -> Vec<&str> {
+    path.split(['/', '\\'])
+        .filter(|component| !component.is_empty())
+        .collect()
+}
+
+pub fn path_depth(path: &str) -> u64 {
+    path_components(path).len() as u64
+}
+
+/**
+ * `C:\Users\me` becomes the facet `/C:/Users/me` and `/home/me` becomes `/home/me`
+ */
+pub fn path_to_facet(path: &str) -> Facet {
+    Facet::from_path(path_components(path))
+}
+
+/**
+ * The facet of the directory that contains the file
+ */
+pub fn parent_directory_facet(file_path: &str) -> Facet {
+    let components = path_components(file_path);
+    let parent = &components[..components.len().saturating_sub(1)];
+    Facet::from_path(parent.iter().copied())
+}
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AggregationsDTO {
+    /// Extensions without the leading dot. Files without an extension have an empty key
+    pub extensions: Vec<BucketDTO>,
+    /// Top-level directories, e.g. `/home` or `/C:`
+    pub directories: Vec<BucketDTO>,
+    /// Years in which the files were last modified
+    pub years: Vec<BucketDTO>,
+    pub sizes: Vec<BucketDTO>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BucketDTO {
+    pub key: String,
+    pub count: u64,
+}
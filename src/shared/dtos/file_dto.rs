@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use super::{highlight_dto::HighlightDTO, source_scores_dto::SourceScoresDTO};
 
 #[derive(Clone, Deserialize, Serialize)]
 #[serde(rename_all = "PascalCase")]
@@ -8,5 +9,12 @@ pub struct FileDTO {
     pub metadata: String,
     pub date_modified: String,
     pub score: f64,
+    pub extension: String,
+    pub category: String,
+    pub size: u64,
+    /// Only filled in when the search asked for highlights
+    pub highlights: Vec<HighlightDTO>,
+    /// Only filled in by hybrid searches
+    pub source_scores: Option<SourceScoresDTO>,
     pub is_directory:bool
 }
\ No newline at end of file
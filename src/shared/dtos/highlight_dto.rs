@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct HighlightDTO {
+    pub field: String,
+    pub fragment: String,
+    /// Byte offsets of every match within `fragment`, as (start, end) pairs
+    pub ranges: Vec<(usize, usize)>,
+}
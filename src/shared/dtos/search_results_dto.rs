@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+use super::{aggregations_dto::AggregationsDTO, file_dto::FileDTO};
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SearchResultsDTO {
+    pub results: Vec<FileDTO>,
+    /// The number of documents matching the query, not just the ones in this page
+    pub total_hits: usize,
+    pub offset: usize,
+    /// Only filled in when the search asked for aggregations
+    pub aggregations: Option<AggregationsDTO>,
+    /// Corrected names to try when nothing matched the name as typed, best first
+    pub did_you_mean: Vec<String>,
+    /// Set when the results are for the best correction rather than the name as typed
+    pub corrected_name: Option<String>,
+}
@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+/**
+ * Where a hybrid search result ranked in each of the searches it was merged from. A source the file
+ * didn't come up in is left empty
+ */
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SourceScoresDTO {
+    pub keyword_rank: Option<usize>,
+    /// The score of the keyword search, popularity and recency included
+    pub keyword_score: Option<f64>,
+    pub semantic_rank: Option<usize>,
+    /// The similarity between the embeddings of the query and the file name
+    pub semantic_score: Option<f32>,
+}
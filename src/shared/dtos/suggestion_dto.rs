@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SuggestionDTO {
+    /// A word from the file names, in lowercase
+    pub term: String,
+    /// How many files have the word in their name
+    pub count: u64,
+    pub score: f64,
+}
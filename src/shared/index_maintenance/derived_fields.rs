@@ -0,0 +1,52 @@
+use std::path::Path;
+
+use tantivy::{schema::Schema, TantivyDocument};
+
+use crate::shared::{
+    converters::path_converter::{parent_directory_facet, path_depth},
+    index_maintenance::file_key::{file_key, FILE_KEY_FIELD},
+    models::file_category::{file_extension, FileCategory},
+};
+
+/**
+ * Every field that `add_derived_fields` can fill in, so a rebuild doesn't need to copy them
+ */
+pub const DERIVED_FIELDS: [&str; 6] = [
+    FILE_KEY_FIELD,
+    "name_raw",
+    "path_depth",
+    "directory",
+    "extension",
+    "category",
+];
+
+/**
+ * Adds the fields that are computed from the name and path instead of being stored. These can't be
+ * copied over when reindexing, so every document has to get them through here.
+ *
+ * The extension and category are only derived when the document doesn't have them yet, since the
+ * crawler knows better for directories, e.g. `photos.2023`
+ */
+pub fn add_derived_fields(doc: &mut TantivyDocument, schema: &Schema, name: &str, file_path: &str) {
+    doc.add_text(
+        schema.get_field(FILE_KEY_FIELD).unwrap(),
+        file_key(file_path),
+    );
+    doc.add_text(schema.get_field("name_raw").unwrap(), name.to_lowercase());
+    doc.add_u64(
+        schema.get_field("path_depth").unwrap(),
+        path_depth(file_path),
+    );
+    doc.add_facet(
+        schema.get_field("directory").unwrap(),
+        parent_directory_facet(file_path),
+    );
+
+    let extension_field = schema.get_field("extension").unwrap();
+    if doc.get_first(extension_field).is_none() {
+        let extension = file_extension(Path::new(name));
+        let category = FileCategory::from_extension(&extension);
+        doc.add_text(schema.get_field("category").unwrap(), category.as_str());
+        doc.add_text(extension_field, extension);
+    }
+}
@@ -0,0 +1,45 @@
+use tantivy::{schema::Schema, Term};
+
+/**
+ * The untokenized field that identifies a document. Every document has exactly one value for it, so
+ * deleting its term before adding a file replaces the file instead of duplicating it
+ */
+pub const FILE_KEY_FIELD: &str = "file_key";
+
+/**
+ * The primary key of the file at `file_path`. Indexing and removal both have to go through here so
+ * that they agree on it, e.g. `C:\Users\me\` and `C:\Users\me` are the same directory
+ */
+pub fn file_key(file_path: &str) -> &str {
+    let trimmed = file_path.trim_end_matches(['/', '\\']);
+    // The root of a Unix filesystem would otherwise be empty
+    if trimmed.is_empty() {
+        file_path
+    } else {
+        trimmed
+    }
+}
+
+pub fn file_key_term(schema: &Schema, file_path: &str) -> tantivy::Result<Term> {
+    let field = schema.get_field(FILE_KEY_FIELD)?;
+    Ok(Term::from_field_text(field, file_key(file_path)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filesindex::infrastructure::searchindex::schemas::file_schema::create_schema;
+
+    #[test]
+    fn trailing_separators_give_the_same_key() {
+        assert_eq!(file_key("C:\\a\\"), file_key("C:\\a"));
+        assert_eq!(file_key("/home/me/"), "/home/me");
+        assert_eq!(file_key("/"), "/");
+
+        let schema = create_schema();
+        assert_eq!(
+            file_key_term(&schema, "C:\\a\\").unwrap(),
+            file_key_term(&schema, "C:\\a").unwrap()
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+use tantivy::{schema::Schema, Index, IndexReader, IndexWriter};
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+use crate::shared::index_maintenance::{reindexer, schema_version::write_schema_version};
+
+/**
+ * Opens the writer that files are indexed with. If the schema of `index` is out of date, the writer
+ * belongs to a new index that is rebuilt in the background instead, and `reader` is pointed at it
+ * once the rebuild is done. Has to be called from within a Tokio runtime
+ */
+pub fn open_writer(
+    index: Index,
+    index_path: &Path,
+    schema: &Schema,
+    buffer_size: usize,
+    reader: Arc<RwLock<IndexReader>>,
+) -> tantivy::Result<Arc<Mutex<IndexWriter>>> {
+    if !reindexer::needs_reindex(index_path, &index, schema) {
+        write_schema_version(index_path)?;
+        return Ok(Arc::new(Mutex::new(index.writer(buffer_size)?)));
+    }
+
+    println!(
+        "Index schema is out of date, rebuilding {:?} while searching the old one",
+        index_path
+    );
+    let new_index = reindexer::create_rebuild_index(index_path, schema)?;
+    let writer = Arc::new(Mutex::new(new_index.writer(buffer_size)?));
+    // Nothing else has the writer yet, so the rebuild is the first to write
+    let writer_guard = writer.clone().try_lock_owned().unwrap();
+    let schema = Arc::new(schema.clone());
+    let index_path = index_path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        rebuild(index, new_index, writer_guard, reader, schema, index_path);
+    });
+    Ok(writer)
+}
+
+/**
+ * Copies the documents of `old_index` into `new_index` through `writer`, then points `reader` at the
+ * new index. Searches keep going to the old index until then.
+ *
+ * The writer stays locked until the reader is swapped, so that crawled files can't be replaced by
+ * their old copy and nothing reads the old index to write into the new one
+ */
+fn rebuild(
+    old_index: Index,
+    new_index: Index,
+    mut writer: OwnedMutexGuard<IndexWriter>,
+    reader: Arc<RwLock<IndexReader>>,
+    schema: Arc<Schema>,
+    index_path: PathBuf,
+) {
+    let num_docs = match copy_and_commit(&old_index, &mut writer, &schema) {
+        Ok(num_docs) => num_docs,
+        Err(err) => {
+            println!(
+                "Error rebuilding index, searching the old one until the next start: {}",
+                err
+            );
+            return;
+        }
+    };
+    // Without it the rebuild is thrown away and started over on the next start
+    if let Err(err) = reindexer::complete_rebuild(&index_path) {
+        println!("Error marking the rebuild as complete: {}", err);
+    }
+
+    match new_index.reader() {
+        Ok(new_reader) => *reader.write().unwrap() = new_reader,
+        Err(err) => {
+            println!("Error opening the rebuilt index: {}", err);
+            return;
+        }
+    }
+    println!("Rebuilt the index with {} documents", num_docs);
+}
+
+fn copy_and_commit(
+    old_index: &Index,
+    writer: &mut IndexWriter,
+    schema: &Schema,
+) -> tantivy::Result<usize> {
+    let num_docs = reindexer::copy_documents(&old_index.reader()?.searcher(), schema, writer)?;
+    writer.commit()?;
+    Ok(num_docs)
+}
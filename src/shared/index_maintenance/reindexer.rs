@@ -0,0 +1,199 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use tantivy::{
+    schema::{Schema, Value},
+    Document, Index, IndexWriter, Searcher, TantivyDocument,
+};
+
+use crate::shared::{
+    index_maintenance::{
+        derived_fields::{add_derived_fields, DERIVED_FIELDS},
+        schema_version::{read_schema_version, version_path, write_schema_version, SCHEMA_VERSION},
+    },
+    tokenizers::filename_tokenizer::register_filename_tokenizer,
+};
+
+/**
+ * Tantivy persists the schema alongside the index, so an index created before a change to
+ * `create_schema` keeps its old field options (tokenizers included) until it is rebuilt. Indexes
+ * from before versions were stored are only rebuilt if their schema differs
+ */
+pub fn needs_reindex(index_path: &Path, index: &Index, schema: &Schema) -> bool {
+    let outdated_version =
+        read_schema_version(index_path).is_some_and(|version| version != SCHEMA_VERSION);
+    outdated_version || index.schema() != *schema
+}
+
+/**
+ * The fields of `schema` that a rebuild from an index with `old_schema` leaves empty, because the
+ * old index doesn't store them and they can't be derived. Files have to be indexed again to fill
+ * them in, e.g. the size of files from before sizes were indexed
+ */
+pub fn uncopied_fields<'a>(old_schema: &Schema, schema: &'a Schema) -> Vec<&'a str> {
+    schema
+        .fields()
+        .map(|(_, field_entry)| field_entry.name())
+        .filter(|field_name| !DERIVED_FIELDS.contains(field_name))
+        .filter(|field_name| {
+            !old_schema
+                .get_field(field_name)
+                .is_ok_and(|field| old_schema.get_field_entry(field).is_stored())
+        })
+        .collect()
+}
+
+/**
+ * Where the index at `index_path` is rebuilt, e.g. `TantivyOut.reindex`
+ */
+pub fn rebuild_path(index_path: &Path) -> PathBuf {
+    index_path.with_extension("reindex")
+}
+
+/**
+ * Creates an empty index with `schema` at the rebuild path, removing what an interrupted rebuild
+ * left there
+ */
+pub fn create_rebuild_index(index_path: &Path, schema: &Schema) -> tantivy::Result<Index> {
+    let rebuild_path = rebuild_path(index_path);
+    if rebuild_path.exists() {
+        fs::remove_dir_all(&rebuild_path)?;
+    }
+    fs::create_dir_all(&rebuild_path)?;
+    let index = Index::create_in_dir(&rebuild_path, schema.clone())?;
+    register_filename_tokenizer(&index);
+    Ok(index)
+}
+
+/**
+ * Adds every stored document of `old_searcher` to `writer`, re-tokenizing them with `schema`. Fields
+ * that no longer exist are dropped, and fields that aren't stored are derived again from the name
+ * and path. Nothing is committed
+ */
+pub fn copy_documents(
+    old_searcher: &Searcher,
+    schema: &Schema,
+    writer: &IndexWriter,
+) -> tantivy::Result<usize> {
+    let old_schema = old_searcher.schema();
+    let mut num_docs: usize = 0;
+    for segment_reader in old_searcher.segment_readers() {
+        let store_reader = segment_reader.get_store_reader(1)?;
+        for doc_id in segment_reader.doc_ids_alive() {
+            let old_doc: TantivyDocument = store_reader.get(doc_id)?;
+            let mut new_doc = TantivyDocument::new();
+            for (field, value) in old_doc.iter_fields_and_values() {
+                if let Ok(new_field) = schema.get_field(old_schema.get_field_name(field)) {
+                    new_doc.add_field_value(new_field, value.clone());
+                }
+            }
+            let name = stored_text(&old_doc, old_schema, "name");
+            let file_path = stored_text(&old_doc, old_schema, "path");
+            add_derived_fields(&mut new_doc, schema, &name, &file_path);
+            writer.add_document(new_doc)?;
+            num_docs += 1;
+        }
+    }
+    Ok(num_docs)
+}
+
+/**
+ * Marks the index at the rebuild path as complete. It replaces the old one the next time
+ * `finish_pending_rebuild` runs
+ */
+pub fn complete_rebuild(index_path: &Path) -> std::io::Result<()> {
+    write_schema_version(&rebuild_path(index_path))
+}
+
+/**
+ * Swaps in a rebuild that was completed while the old index was still open, or removes one that was
+ * interrupted. Has to run before the index at `index_path` is opened
+ */
+pub fn finish_pending_rebuild(index_path: &Path) -> std::io::Result<()> {
+    let rebuild_path = rebuild_path(index_path);
+    if !rebuild_path.exists() {
+        return Ok(());
+    }
+    if read_schema_version(&rebuild_path) != Some(SCHEMA_VERSION) {
+        println!("Removing unfinished rebuild at {:?}", rebuild_path);
+        return fs::remove_dir_all(&rebuild_path);
+    }
+
+    println!("Replacing {:?} with its rebuild", index_path);
+    if index_path.exists() {
+        fs::remove_dir_all(index_path)?;
+    }
+    fs::rename(&rebuild_path, index_path)?;
+    fs::rename(version_path(&rebuild_path), version_path(index_path))
+}
+
+fn stored_text(doc: &TantivyDocument, schema: &Schema, field_name: &str) -> String {
+    schema
+        .get_field(field_name)
+        .ok()
+        .and_then(|field| doc.get_first(field))
+        .and_then(|value| value.as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::{
+        collector::TopDocs,
+        doc,
+        query::AllQuery,
+        schema::{FAST, INDEXED, STORED, TEXT},
+    };
+
+    use super::*;
+    use crate::filesindex::infrastructure::searchindex::schemas::file_schema::create_schema;
+
+    // The schema from before the index had extensions, categories and sizes
+    fn baseline_schema() -> Schema {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("file_id", TEXT | STORED);
+        schema_builder.add_text_field("name", TEXT | STORED);
+        schema_builder.add_date_field("date_modified", INDEXED | STORED);
+        schema_builder.add_text_field("metadata", TEXT | STORED);
+        schema_builder.add_text_field("path", TEXT | STORED);
+        schema_builder.add_f64_field("popularity", FAST | STORED);
+        schema_builder.build()
+    }
+
+    #[test]
+    fn baseline_documents_get_derived_fields_and_lose_only_their_size() {
+        let old_schema = baseline_schema();
+        let old_index = Index::create_in_ram(old_schema.clone());
+        let mut old_writer = old_index.writer::<TantivyDocument>(15_000_000).unwrap();
+        old_writer
+            .add_document(doc!(
+                old_schema.get_field("name").unwrap() => "Report.PDF",
+                old_schema.get_field("path").unwrap() => "/docs/Report.PDF",
+            ))
+            .unwrap();
+        old_writer.commit().unwrap();
+
+        let schema = create_schema();
+        assert_eq!(uncopied_fields(&old_schema, &schema), vec!["size"]);
+
+        let new_index = Index::create_in_ram(schema.clone());
+        register_filename_tokenizer(&new_index);
+        let mut writer = new_index.writer::<TantivyDocument>(15_000_000).unwrap();
+        let old_searcher = old_index.reader().unwrap().searcher();
+        assert_eq!(copy_documents(&old_searcher, &schema, &writer).unwrap(), 1);
+        writer.commit().unwrap();
+
+        let searcher = new_index.reader().unwrap().searcher();
+        let (_, doc_address) = searcher.search(&AllQuery, &TopDocs::with_limit(1)).unwrap()[0];
+        let doc: TantivyDocument = searcher.doc(doc_address).unwrap();
+        assert_eq!(stored_text(&doc, &schema, "extension"), "pdf");
+        assert_eq!(stored_text(&doc, &schema, "category"), "document");
+        assert_eq!(
+            doc.get_all(schema.get_field("extension").unwrap()).count(),
+            1
+        );
+    }
+}
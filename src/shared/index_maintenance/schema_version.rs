@@ -0,0 +1,33 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/**
+ * Has to be bumped whenever `create_schema` changes. Indexes written with another version are
+ * rebuilt on startup
+ */
+pub const SCHEMA_VERSION: u32 = 1;
+
+/**
+ * The version is kept next to the index directory rather than inside it, since Tantivy owns the
+ * files in there, e.g. `TantivyOut.version` for `TantivyOut`
+ */
+pub fn version_path(index_path: &Path) -> PathBuf {
+    let mut file_name = index_path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".version");
+    index_path.with_file_name(file_name)
+}
+
+/**
+ * `None` if the index was created before versions were stored, or the file can't be read
+ */
+pub fn read_schema_version(index_path: &Path) -> Option<u32> {
+    fs::read_to_string(version_path(index_path))
+        .ok()
+        .and_then(|contents| contents.trim().parse().ok())
+}
+
+pub fn write_schema_version(index_path: &Path) -> io::Result<()> {
+    fs::write(version_path(index_path), SCHEMA_VERSION.to_string())
+}
@@ -1,6 +1,51 @@
 pub mod dtos{
+    pub mod aggregations_dto;
     pub mod file_dto;
+    pub mod highlight_dto;
+    pub mod search_results_dto;
+    pub mod source_scores_dto;
+    pub mod suggestion_dto;
+}
+pub mod models{
+    pub mod field_boosts;
+    pub mod file_category;
+    pub mod fusion_weights;
+    pub mod score_weights;
+    pub mod search_params_model;
+}
+pub mod converters{
+    pub mod glob_converter;
+    pub mod path_converter;
 }
 pub mod collections{
     pub mod popularity_set;
-}
\ No newline at end of file
+}
+pub mod scorers{
+    pub mod file_scorer;
+    pub mod pop_scorer;
+    pub mod recency_scorer;
+    pub mod size_scorer;
+    pub mod weighted_scorer;
+}
+pub mod tokenizers{
+    pub mod filename_tokenizer;
+}
+pub mod aggregations{
+    pub mod file_aggregations;
+}
+pub mod queries{
+    pub mod file_lookup;
+    pub mod file_query;
+    pub mod similar_files;
+}
+pub mod suggestions{
+    pub mod name_suggestions;
+    pub mod spelling_corrections;
+}
+pub mod index_maintenance{
+    pub mod derived_fields;
+    pub mod file_key;
+    pub mod index_rebuilder;
+    pub mod reindexer;
+    pub mod schema_version;
+}
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+/**
+ * Multiplies the text relevance of a hit depending on the field it was found in, so that a word in
+ * the file name counts for more than the same word in a parent directory
+ */
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct FieldBoosts {
+    pub name: f32,
+    pub path: f32,
+    pub metadata: f32,
+    /// Added when the whole name is typed out, ignoring case, e.g. `cargo.toml`
+    pub exact_name_bonus: f32,
+}
+
+impl Default for FieldBoosts {
+    fn default() -> Self {
+        Self {
+            name: 3.0,
+            path: 1.0,
+            metadata: 0.5,
+            exact_name_bonus: 5.0,
+        }
+    }
+}
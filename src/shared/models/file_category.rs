@@ -0,0 +1,94 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum FileCategory {
+    Document,
+    Image,
+    Video,
+    Audio,
+    Archive,
+    Code,
+    Executable,
+    Other,
+}
+
+impl FileCategory {
+    /**
+     * Expects a lowercase extension without the leading dot, as returned by `file_extension`
+     */
+    pub fn from_extension(extension: &str) -> Self {
+        match extension {
+            "pdf" | "doc" | "docx" | "odt" | "rtf" | "txt" | "md" | "xls" | "xlsx" | "ods"
+            | "csv" | "ppt" | "pptx" | "odp" | "epub" | "pages" | "numbers" | "key" | "tex" => {
+                FileCategory::Document
+            }
+            "jpg" | "jpeg" | "png" | "gif" | "bmp" | "tif" | "tiff" | "webp" | "svg" | "ico"
+            | "heic" | "heif" | "raw" | "cr2" | "nef" | "psd" => FileCategory::Image,
+            "mp4" | "mkv" | "avi" | "mov" | "wmv" | "flv" | "webm" | "m4v" | "mpg" | "mpeg"
+            | "3gp" => FileCategory::Video,
+            "mp3" | "wav" | "flac" | "aac" | "ogg" | "oga" | "m4a" | "wma" | "opus" | "aiff"
+            | "mid" | "midi" => FileCategory::Audio,
+            "zip" | "rar" | "7z" | "tar" | "gz" | "tgz" | "bz2" | "xz" | "zst" | "iso" | "dmg"
+            | "cab" => FileCategory::Archive,
+            "rs" | "py" | "js" | "ts" | "jsx" | "tsx" | "java" | "c" | "h" | "cpp" | "hpp"
+            | "cc" | "cs" | "go" | "rb" | "php" | "swift" | "kt" | "scala" | "lua" | "sh"
+            | "ps1" | "sql" | "html" | "css" | "scss" | "json" | "toml" | "yaml" | "yml"
+            | "xml" => FileCategory::Code,
+            "exe" | "msi" | "bat" | "cmd" | "com" | "dll" | "so" | "app" | "apk" | "deb"
+            | "rpm" | "appimage" | "bin" => FileCategory::Executable,
+            _ => FileCategory::Other,
+        }
+    }
+
+    /**
+     * The value stored in the index's `category` field
+     */
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FileCategory::Document => "document",
+            FileCategory::Image => "image",
+            FileCategory::Video => "video",
+            FileCategory::Audio => "audio",
+            FileCategory::Archive => "archive",
+            FileCategory::Code => "code",
+            FileCategory::Executable => "executable",
+            FileCategory::Other => "other",
+        }
+    }
+
+    /**
+     * The inverse of `as_str`, ignoring case
+     */
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "document" => Some(FileCategory::Document),
+            "image" => Some(FileCategory::Image),
+            "video" => Some(FileCategory::Video),
+            "audio" => Some(FileCategory::Audio),
+            "archive" => Some(FileCategory::Archive),
+            "code" => Some(FileCategory::Code),
+            "executable" => Some(FileCategory::Executable),
+            "other" => Some(FileCategory::Other),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * Returns the lowercase extension of the path without the leading dot, or an empty string if it
+ * has none
+ */
+pub fn file_extension(path: &Path) -> String {
+    path.extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+/**
+ * Normalizes an extension typed by the user, so that `.PDF` and `pdf` are the same filter
+ */
+pub fn normalize_extension(extension: &str) -> String {
+    extension.trim().trim_start_matches('.').to_lowercase()
+}
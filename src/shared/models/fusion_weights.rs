@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+/**
+ * How the keyword and semantic rankings are merged. A file scores `weight / (rank_constant + rank)`
+ * in each ranking it appears in, with ranks starting at 1
+ */
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct FusionWeights {
+    pub keyword: f64,
+    pub semantic: f64,
+    /// Higher values flatten the difference between the top ranks and the ones below them
+    pub rank_constant: f64,
+}
+
+impl Default for FusionWeights {
+    fn default() -> Self {
+        Self {
+            keyword: 1.0,
+            semantic: 1.0,
+            rank_constant: 60.0,
+        }
+    }
+}
+
+impl FusionWeights {
+    pub fn keyword_score(&self, rank: usize) -> f64 {
+        self.keyword / (self.rank_constant + rank as f64)
+    }
+
+    pub fn semantic_score(&self, rank: usize) -> f64 {
+        self.semantic / (self.rank_constant + rank as f64)
+    }
+}
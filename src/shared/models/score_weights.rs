@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+/**
+ * How much each part of the ranking counts towards a file's final score. Fields left out of a
+ * request fall back to the defaults below
+ */
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct ScoreWeights {
+    /// Multiplies the BM25 score of the text match
+    pub relevance: f64,
+    /// Multiplies `log10(popularity)`
+    pub popularity: f64,
+    /// Multiplies the recency score, which is 1 for a file modified just now
+    pub recency: f64,
+    /// How many days it takes for the recency score to halve
+    pub recency_half_life_days: f64,
+}
+
+impl Default for ScoreWeights {
+    fn default() -> Self {
+        Self {
+            relevance: 1.0,
+            popularity: 1.0,
+            recency: 1.0,
+            recency_half_life_days: 30.0,
+        }
+    }
+}
+
+impl ScoreWeights {
+    pub fn combine(&self, relevance: f32, popularity: f64, recency: f64) -> f64 {
+        self.relevance * relevance as f64 + self.popularity * popularity + self.recency * recency
+    }
+}
@@ -0,0 +1,195 @@
+use serde::{Deserialize, Serialize};
+use tantivy::{query::Occur, time::OffsetDateTime, Order};
+
+use crate::shared::models::{file_category::FileCategory, score_weights::ScoreWeights};
+
+pub const DEFAULT_LIMIT: usize = 10;
+pub const MAX_LIMIT: usize = 1000;
+// Tantivy allocates room for twice the offset plus the limit up front, so pages can't go past this
+pub const MAX_RESULT_WINDOW: usize = 10_000;
+// Tantivy only builds Levenshtein automatons for distances up to 2
+pub const MAX_FUZZY_DISTANCE: u8 = 2;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct SearchParamsModel {
+    pub name: Option<String>,
+    pub metadata: Option<String>,
+    pub date_range: Option<DateRange>,
+    pub size_range: Option<SizeRange>,
+    pub file_path: Option<String>,
+    /// Whether files have to match both the name and the path, and every word of them
+    #[serde(default)]
+    pub match_mode: MatchMode,
+    /// Matches the whole name against a glob or regex, ignoring case
+    pub name_pattern: Option<NamePattern>,
+    /// Leaves out files whose name matches any of these
+    pub exclude_names: Option<Vec<String>>,
+    /// Leaves out files whose path matches any of these
+    pub exclude_paths: Option<Vec<String>>,
+    /// Leaves out files with any of these extensions
+    pub exclude_extensions: Option<Vec<String>>,
+    /// Only return files inside this directory
+    pub directory: Option<DirectoryScope>,
+    /// Only return files with one of these extensions, e.g. `pdf` or `.PDF`
+    pub extensions: Option<Vec<String>>,
+    /// Only return files that belong to one of these categories
+    pub categories: Option<Vec<FileCategory>>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+    /// When set, the name is also matched with typo tolerance. Files matching it as typed come first
+    pub fuzzy: Option<FuzzyOptions>,
+    /// Treats the last word of the name as a prefix, for search-as-you-type
+    #[serde(default)]
+    pub prefix: bool,
+    /// Overrides the configured weights of relevance, popularity and recency in the ranking
+    pub score_weights: Option<ScoreWeights>,
+    /// The ranking function used when ordering by relevance, one of `SCORER_NAMES`. Defaults to
+    /// `weighted`, which combines relevance, popularity and recency
+    pub scorer: Option<String>,
+    /// Orders the results by a field instead of by relevance, which is the default
+    pub sort_by: Option<SortOptions>,
+    /// Returns highlighted fragments of the name, path and metadata that matched.
+    /// Fuzzy and prefix matches have no exact terms, so they are not highlighted
+    #[serde(default)]
+    pub highlight: bool,
+    /// Counts the matching files per extension, top-level directory, modified year and size
+    #[serde(default)]
+    pub aggregations: bool,
+    /// When nothing matches the name, searches again with its best spelling correction
+    #[serde(default)]
+    pub auto_correct: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct DateRange {
+    pub start: OffsetDateTime,
+    pub end: OffsetDateTime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct DirectoryScope {
+    /// Either a Windows or a Unix path
+    pub path: String,
+    /// Whether files in subdirectories are included, rather than only the directory's own files
+    #[serde(default = "default_true")]
+    pub recursive: bool,
+}
+
+/**
+ * Both bounds are inclusive and in bytes
+ */
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct SizeRange {
+    pub min: Option<u64>,
+    pub max: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct SortOptions {
+    pub field: SortField,
+    #[serde(default)]
+    pub direction: SortDirection,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum SortField {
+    DateModified,
+    Name,
+    Size,
+    /// How many directories deep the file is
+    PathDepth,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub enum SortDirection {
+    Ascending,
+    #[default]
+    Descending,
+}
+
+impl SortDirection {
+    pub fn to_order(self) -> Order {
+        match self {
+            SortDirection::Ascending => Order::Asc,
+            SortDirection::Descending => Order::Desc,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct NamePattern {
+    pub pattern: String,
+    pub syntax: PatternSyntax,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternSyntax {
+    /// e.g. `*.log.[0-9]`
+    Glob,
+    /// e.g. `^IMG_\d{4}\.jpe?g$`. Patterns are always anchored to the whole name
+    Regex,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MatchMode {
+    /// A file only has to match one word of the name or path, the rest adds to its score
+    #[default]
+    Any,
+    All,
+}
+
+impl MatchMode {
+    /**
+     * How the name and path clauses are combined with each other
+     */
+    pub fn occur(self) -> Occur {
+        match self {
+            MatchMode::Any => Occur::Should,
+            MatchMode::All => Occur::Must,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct FuzzyOptions {
+    /// Maximum number of edits per term, capped at `MAX_FUZZY_DISTANCE`
+    pub distance: u8,
+    /// Whether swapping two adjacent characters counts as a single edit
+    #[serde(default = "default_true")]
+    pub transposition: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl FuzzyOptions {
+    pub fn get_distance(&self) -> u8 {
+        self.distance.min(MAX_FUZZY_DISTANCE)
+    }
+}
+
+impl SearchParamsModel {
+    /**
+     * The number of results to return, clamped so that a page is never empty or unbounded
+     */
+    pub fn get_limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+    }
+
+    /**
+     * The number of results to skip, clamped so that the page ends within `MAX_RESULT_WINDOW`
+     */
+    pub fn get_offset(&self) -> usize {
+        self.offset
+            .unwrap_or(0)
+            .min(MAX_RESULT_WINDOW - self.get_limit())
+    }
+}
@@ -0,0 +1,18 @@
+use tantivy::{
+    collector::TopDocs, query::TermQuery, schema::IndexRecordOption, DocAddress, Searcher,
+};
+
+use crate::shared::index_maintenance::file_key::file_key_term;
+
+/**
+ * Finds the document of the file at `file_path` by its primary key
+ */
+pub fn find_file(searcher: &Searcher, file_path: &str) -> tantivy::Result<Option<DocAddress>> {
+    let term = file_key_term(searcher.schema(), file_path)?;
+    let query = TermQuery::new(term, IndexRecordOption::Basic);
+    let top_docs = searcher.search(&query, &TopDocs::with_limit(1))?;
+    Ok(top_docs
+        .into_iter()
+        .next()
+        .map(|(_, doc_address)| doc_address))
+}
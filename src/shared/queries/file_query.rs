@@ -0,0 +1,857 @@
+use std::{cmp::Reverse, ops::Bound, sync::Arc};
+use tantivy::{
+    collector::{Collector, Count, TopDocs},
+    query::{
+        AllQuery, BooleanQuery, BoostQuery, ConstScoreQuery, FuzzyTermQuery, Occur, Query,
+        QueryParser, RangeQuery, RegexQuery, TermQuery, TermSetQuery,
+    },
+    schema::{Field, IndexRecordOption},
+    snippet::SnippetGenerator,
+    DateTime, DocAddress, DocId, Index, Searcher, SegmentReader, TantivyDocument, TantivyError,
+    Term,
+};
+
+use crate::shared::{
+    aggregations::file_aggregations::{aggregations_to_dto, file_aggregations_collector},
+    converters::{
+        glob_converter::glob_to_regex,
+        path_converter::{path_depth, path_to_facet},
+    },
+    dtos::{aggregations_dto::AggregationsDTO, highlight_dto::HighlightDTO},
+    models::{
+        field_boosts::FieldBoosts,
+        file_category::normalize_extension,
+        score_weights::ScoreWeights,
+        search_params_model::{
+            FuzzyOptions, MatchMode, NamePattern, PatternSyntax, SearchParamsModel, SortDirection,
+            SortField, SortOptions,
+        },
+    },
+    scorers::file_scorer::{scorer_by_name, FileScorer, DEFAULT_SCORER, SCORER_NAMES},
+    suggestions::spelling_corrections::spelling_corrections,
+};
+
+const FUZZY_MATCH_SCORE: f32 = 0.5;
+
+const HIGHLIGHT_FIELDS: [&str; 3] = ["name", "path", "metadata"];
+
+// Documents paired with the score they are ranked by
+pub type RankedDocs = Vec<(f64, DocAddress)>;
+
+/**
+ * A page of matching documents, along with what was counted over every match
+ */
+pub struct FileHits {
+    pub docs: RankedDocs,
+    /// The number of documents matching the query, not just the ones in this page
+    pub total_hits: usize,
+    pub aggregations: Option<AggregationsDTO>,
+}
+
+/**
+ * Turns the search params into a single query, without running it. `field_boosts` weighs each
+ * text match by the field it was found in
+ */
+pub fn build_file_query(
+    searcher: &Searcher,
+    search_params: &SearchParamsModel,
+    field_boosts: &FieldBoosts,
+) -> tantivy::Result<BooleanQuery> {
+    let schema = searcher.schema();
+    let mut queries: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+    // Grouped so that they still have to match in `Any` mode, alongside the required filters
+    let mut text_queries: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+
+    let match_mode = search_params.match_mode;
+
+    if let Some(file_path) = &search_params.file_path {
+        let field = schema.get_field("path")?;
+        let query_parser = text_query_parser(searcher.index(), field, match_mode);
+        let query = query_parser.parse_query(file_path)?;
+        let query = BoostQuery::new(query, field_boosts.path);
+        text_queries.push((match_mode.occur(), Box::new(query)));
+    }
+
+    if let Some(directory) = &search_params.directory {
+        let field = schema.get_field("directory")?;
+        let facet = path_to_facet(&directory.path);
+        // Facets match all of their descendants, so this alone is a recursive search
+        let term = Term::from_facet(field, &facet);
+        queries.push((
+            Occur::Must,
+            Box::new(TermQuery::new(term, IndexRecordOption::Basic)),
+        ));
+
+        if !directory.recursive {
+            let depth = path_depth(&directory.path) + 1;
+            let query = RangeQuery::new_u64_bounds(
+                "path_depth".to_string(),
+                Bound::Included(depth),
+                Bound::Included(depth),
+            );
+            queries.push((Occur::Must, Box::new(query)));
+        }
+    }
+
+    if let Some(query_str) = &search_params.name {
+        let field = schema.get_field("name")?;
+        let query = name_query(searcher, field, query_str, search_params)?;
+        let query = match &search_params.fuzzy {
+            Some(fuzzy) => fuzzy_name_query(
+                searcher.index(),
+                field,
+                query,
+                query_str,
+                search_params.prefix,
+                fuzzy,
+                match_mode,
+            )?,
+            None => query,
+        };
+        let query = BoostQuery::new(query, field_boosts.name);
+        text_queries.push((match_mode.occur(), Box::new(query)));
+
+        // Only a bonus, so it is left out while an index from before `name_raw` is searched
+        if let Ok(name_raw_field) = schema.get_field("name_raw") {
+            let exact_name_bonus = field_boosts.exact_name_bonus;
+            if let Some(query) = exact_name_query(name_raw_field, query_str, exact_name_bonus) {
+                queries.push((Occur::Should, query));
+            }
+        }
+    }
+
+    if !text_queries.is_empty() {
+        queries.push((Occur::Must, Box::new(BooleanQuery::new(text_queries))));
+    }
+
+    if let Some(name_pattern) = &search_params.name_pattern {
+        let field = schema.get_field("name_raw")?;
+        queries.push((Occur::Must, name_pattern_query(field, name_pattern)?));
+    }
+
+    if let Some(exclude_names) = &search_params.exclude_names {
+        let field = schema.get_field("name")?;
+        let query_parser = QueryParser::for_index(searcher.index(), vec![field]);
+        for exclude_name in exclude_names {
+            let query = query_parser.parse_query(exclude_name)?;
+            queries.push((Occur::MustNot, query));
+        }
+    }
+
+    if let Some(exclude_paths) = &search_params.exclude_paths {
+        let field = schema.get_field("path")?;
+        let query_parser = QueryParser::for_index(searcher.index(), vec![field]);
+        for exclude_path in exclude_paths {
+            let query = query_parser.parse_query(exclude_path)?;
+            queries.push((Occur::MustNot, query));
+        }
+    }
+
+    if let Some(date_range) = &search_params.date_range {
+        let start_date = DateTime::from_utc(date_range.start);
+        let end_date = DateTime::from_utc(date_range.end);
+        let query = RangeQuery::new_date("date_modified".to_string(), start_date..end_date);
+        queries.push((Occur::Must, Box::new(query)));
+    }
+
+    if let Some(size_range) = &search_params.size_range {
+        let lower = size_range.min.map_or(Bound::Unbounded, Bound::Included);
+        let upper = size_range.max.map_or(Bound::Unbounded, Bound::Included);
+        let query = RangeQuery::new_u64_bounds("size".to_string(), lower, upper);
+        queries.push((Occur::Must, Box::new(query)));
+    }
+
+    if let Some(metadata) = &search_params.metadata {
+        let field = schema.get_field("metadata")?;
+        let term = Term::from_field_text(field, metadata);
+        let query = TermQuery::new(term, IndexRecordOption::Basic);
+        let query = BoostQuery::new(Box::new(query), field_boosts.metadata);
+        queries.push((Occur::Must, Box::new(query)));
+    }
+
+    if let Some(extensions) = search_params.extensions.as_ref().filter(|x| !x.is_empty()) {
+        let field = schema.get_field("extension")?;
+        let terms = extensions
+            .iter()
+            .map(|extension| Term::from_field_text(field, &normalize_extension(extension)));
+        queries.push((Occur::Must, Box::new(TermSetQuery::new(terms))));
+    }
+
+    if let Some(exclude_extensions) = search_params
+        .exclude_extensions
+        .as_ref()
+        .filter(|x| !x.is_empty())
+    {
+        let field = schema.get_field("extension")?;
+        let terms = exclude_extensions
+            .iter()
+            .map(|extension| Term::from_field_text(field, &normalize_extension(extension)));
+        queries.push((Occur::MustNot, Box::new(TermSetQuery::new(terms))));
+    }
+
+    if let Some(categories) = search_params.categories.as_ref().filter(|x| !x.is_empty()) {
+        let field = schema.get_field("category")?;
+        let terms = categories
+            .iter()
+            .map(|category| Term::from_field_text(field, category.as_str()));
+        queries.push((Occur::Must, Box::new(TermSetQuery::new(terms))));
+    }
+
+    // Exclusions alone match nothing, so they are applied to every document instead
+    if !queries.is_empty() && queries.iter().all(|(occur, _)| *occur == Occur::MustNot) {
+        queries.push((Occur::Must, Box::new(AllQuery)));
+    }
+
+    // Combine all the queries into a BooleanQuery
+    Ok(BooleanQuery::new(queries))
+}
+
+/**
+ * Runs `query` and collects the requested page of results, ordered by `search_params.sort_by` or
+ * by the requested scorer. `default_weights` ranks the results unless the search params bring
+ * their own
+ */
+pub fn search_files(
+    searcher: &Searcher,
+    query: &BooleanQuery,
+    search_params: &SearchParamsModel,
+    default_weights: &ScoreWeights,
+) -> tantivy::Result<FileHits> {
+    let top_docs =
+        TopDocs::with_limit(search_params.get_limit()).and_offset(search_params.get_offset());
+
+    let aggregations_collector = search_params.aggregations.then(file_aggregations_collector);
+    let stats_collector = (Count, aggregations_collector);
+
+    // Execute the query and collect the page of results along with the total hit count
+    let (docs, (total_hits, aggregations)) = match &search_params.sort_by {
+        Some(sort) => sorted_search(searcher, query, top_docs, sort, stats_collector)?,
+        None => {
+            let weights = search_params.score_weights.unwrap_or(*default_weights);
+            let scorer = file_scorer(search_params.scorer.as_deref(), weights)?;
+            match exact_name_query_for_tier(searcher, search_params)? {
+                Some(exact_query) => exact_first_search(
+                    searcher,
+                    query,
+                    exact_query,
+                    search_params,
+                    scorer,
+                    stats_collector,
+                )?,
+                None => {
+                    let top_docs_collector =
+                        top_docs.tweak_score(move |segment_reader: &SegmentReader| {
+                            scorer.for_segment(segment_reader.fast_fields())
+                        });
+                    searcher.search(query, &(top_docs_collector, stats_collector))?
+                }
+            }
+        }
+    };
+
+    Ok(FileHits {
+        docs,
+        total_hits,
+        aggregations: aggregations.map(aggregations_to_dto),
+    })
+}
+
+/**
+ * Corrected names to suggest when nothing matched the name as typed
+ */
+pub fn did_you_mean(
+    searcher: &Searcher,
+    search_params: &SearchParamsModel,
+    total_hits: usize,
+) -> tantivy::Result<Vec<String>> {
+    match &search_params.name {
+        Some(name) if total_hits == 0 => spelling_corrections(searcher, name),
+        _ => Ok(Vec::new()),
+    }
+}
+
+/**
+ * The search params to run again when `auto_correct` is set and there is a correction to use
+ */
+pub fn corrected_params(
+    search_params: &SearchParamsModel,
+    did_you_mean: &[String],
+) -> Option<(String, SearchParamsModel)> {
+    if !search_params.auto_correct {
+        return None;
+    }
+    let correction = did_you_mean.first()?;
+    let corrected_params = SearchParamsModel {
+        name: Some(correction.clone()),
+        auto_correct: false,
+        ..search_params.clone()
+    };
+    Some((correction.clone(), corrected_params))
+}
+
+/**
+ * Highlights the terms of `query` in the name, path and metadata of each result, or nothing
+ * unless the search asked for it
+ */
+pub fn snippet_generators(
+    searcher: &Searcher,
+    query: &dyn Query,
+    search_params: &SearchParamsModel,
+) -> tantivy::Result<Vec<(&'static str, SnippetGenerator)>> {
+    if !search_params.highlight {
+        return Ok(Vec::new());
+    }
+    let schema = searcher.schema();
+    HIGHLIGHT_FIELDS
+        .iter()
+        .map(|field_name| {
+            let field = schema.get_field(field_name)?;
+            let generator = SnippetGenerator::create(searcher, query, field)?;
+            Ok((*field_name, generator))
+        })
+        .collect()
+}
+
+// Fields without any matching term are left out
+pub fn highlights_for_doc(
+    generators: &[(&'static str, SnippetGenerator)],
+    doc: &TantivyDocument,
+) -> Vec<HighlightDTO> {
+    generators
+        .iter()
+        .filter_map(|(field_name, generator)| {
+            let snippet = generator.snippet_from_doc(doc);
+            if snippet.is_empty() {
+                return None;
+            }
+            Some(HighlightDTO {
+                field: field_name.to_string(),
+                fragment: snippet.fragment().to_string(),
+                ranges: snippet
+                    .highlighted()
+                    .iter()
+                    .map(|range| (range.start, range.end))
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+/**
+ * Looks up the scorer requested by name, falling back to the default one
+ */
+pub fn file_scorer(
+    name: Option<&str>,
+    weights: ScoreWeights,
+) -> tantivy::Result<Arc<dyn FileScorer>> {
+    let name = name.unwrap_or(DEFAULT_SCORER);
+    scorer_by_name(name, weights).ok_or_else(|| {
+        TantivyError::InvalidArgument(format!(
+            "Unknown scorer '{}', expected one of: {}",
+            name,
+            SCORER_NAMES.join(", ")
+        ))
+    })
+}
+
+/**
+ * The name query without typo tolerance, when the search is fuzzy and its exact matches have to be
+ * ranked on their own
+ */
+fn exact_name_query_for_tier(
+    searcher: &Searcher,
+    search_params: &SearchParamsModel,
+) -> tantivy::Result<Option<Box<dyn Query>>> {
+    match (&search_params.name, &search_params.fuzzy) {
+        (Some(query_str), Some(_)) => {
+            let field = searcher.schema().get_field("name")?;
+            Ok(Some(name_query(searcher, field, query_str, search_params)?))
+        }
+        _ => Ok(None),
+    }
+}
+
+/**
+ * Ranks the files whose name matched without typos above the ones that needed fuzzy matching,
+ * however popular or recent those are. Each tier is ordered by the scorer, and the page is taken
+ * from the exact matches followed by the rest
+ */
+fn exact_first_search<C: Collector>(
+    searcher: &Searcher,
+    query: &BooleanQuery,
+    exact_query: Box<dyn Query>,
+    search_params: &SearchParamsModel,
+    scorer: Arc<dyn FileScorer>,
+    stats_collector: C,
+) -> tantivy::Result<(RankedDocs, C::Fruit)> {
+    let offset = search_params.get_offset();
+    let limit = search_params.get_limit();
+    // Only filters, so that the scores within a tier are the same as without tiers
+    let exact_filter: Box<dyn Query> = Box::new(ConstScoreQuery::new(exact_query, 0.0));
+    let exact_tier = BooleanQuery::new(vec![
+        (Occur::Must, Box::new(query.clone())),
+        (Occur::Must, exact_filter.box_clone()),
+    ]);
+    let fuzzy_tier = BooleanQuery::new(vec![
+        (Occur::Must, Box::new(query.clone())),
+        (Occur::MustNot, exact_filter),
+    ]);
+
+    let stats = searcher.search(query, &stats_collector)?;
+    let mut docs = RankedDocs::new();
+    for tier in [exact_tier, fuzzy_tier] {
+        let scorer = Arc::clone(&scorer);
+        let collector = TopDocs::with_limit(offset + limit).tweak_score(
+            move |segment_reader: &SegmentReader| scorer.for_segment(segment_reader.fast_fields()),
+        );
+        docs.extend(searcher.search(&tier, &collector)?);
+    }
+    let docs = docs.into_iter().skip(offset).take(limit).collect();
+    Ok((docs, stats))
+}
+
+/**
+ * Orders the results by a fast field instead of by relevance. Relevance is not computed in this
+ * case, so every result gets a score of 0
+ */
+fn sorted_search<C: Collector>(
+    searcher: &Searcher,
+    query: &dyn Query,
+    top_docs: TopDocs,
+    sort: &SortOptions,
+    stats_collector: C,
+) -> tantivy::Result<(RankedDocs, C::Fruit)> {
+    let order = sort.direction.to_order();
+    let (addresses, stats) = match sort.field {
+        SortField::DateModified => {
+            let collector = top_docs.order_by_fast_field::<DateTime>("date_modified", order);
+            sorted_addresses(searcher, query, collector, stats_collector)?
+        }
+        SortField::Size => {
+            let collector = top_docs.order_by_fast_field::<u64>("size", order);
+            sorted_addresses(searcher, query, collector, stats_collector)?
+        }
+        SortField::PathDepth => {
+            let collector = top_docs.order_by_fast_field::<u64>("path_depth", order);
+            sorted_addresses(searcher, query, collector, stats_collector)?
+        }
+        // Tantivy can only order by numeric fast fields, so names are compared as strings
+        SortField::Name => match sort.direction {
+            SortDirection::Ascending => {
+                let collector = top_docs.custom_score(|segment_reader: &SegmentReader| {
+                    let name_sort = name_sort_key(segment_reader);
+                    move |doc: DocId| Reverse(name_sort(doc))
+                });
+                sorted_addresses(searcher, query, collector, stats_collector)?
+            }
+            SortDirection::Descending => {
+                let collector = top_docs.custom_score(name_sort_key);
+                sorted_addresses(searcher, query, collector, stats_collector)?
+            }
+        },
+    };
+    let docs = addresses
+        .into_iter()
+        .map(|address| (0.0, address))
+        .collect();
+    Ok((docs, stats))
+}
+
+/**
+ * Runs `query` with a collector that orders the documents by a sort key, keeping only their
+ * addresses
+ */
+fn sorted_addresses<K, S, C>(
+    searcher: &Searcher,
+    query: &dyn Query,
+    sort_collector: S,
+    stats_collector: C,
+) -> tantivy::Result<(Vec<DocAddress>, C::Fruit)>
+where
+    S: Collector<Fruit = Vec<(K, DocAddress)>>,
+    C: Collector,
+{
+    let (docs, stats) = searcher.search(query, &(sort_collector, stats_collector))?;
+    let addresses = docs.into_iter().map(|(_, address)| address).collect();
+    Ok((addresses, stats))
+}
+
+fn name_sort_key(segment_reader: &SegmentReader) -> impl Fn(DocId) -> String {
+    // Indexes from before `name_raw` sort every name as empty
+    let name_sort_field = segment_reader.fast_fields().str("name_raw").ok().flatten();
+    move |doc: DocId| {
+        let mut name = String::new();
+        if let Some(column) = &name_sort_field {
+            if let Some(ord) = column.term_ords(doc).next() {
+                let _ = column.ord_to_str(ord, &mut name);
+            }
+        }
+        name
+    }
+}
+
+/**
+ * Merges the exact name query with a typo tolerant match on each of the name's tokens.
+ *
+ * Fuzzy matches get a constant score, while exact matches keep their BM25 score.
+ * `search_files` ranks the exact matches first
+ */
+fn fuzzy_name_query(
+    index: &Index,
+    field: Field,
+    exact_query: Box<dyn Query>,
+    query_str: &str,
+    prefix: bool,
+    fuzzy: &FuzzyOptions,
+    match_mode: MatchMode,
+) -> tantivy::Result<Box<dyn Query>> {
+    let terms = name_terms(index, field, query_str)?;
+    let partial_last = prefix && ends_with_partial_token(query_str);
+    let last = terms.len().saturating_sub(1);
+
+    let fuzzy_queries: Vec<(Occur, Box<dyn Query>)> = terms
+        .into_iter()
+        .enumerate()
+        .map(|(i, term)| {
+            let query = if i == last && partial_last {
+                FuzzyTermQuery::new_prefix(term, fuzzy.get_distance(), fuzzy.transposition)
+            } else {
+                FuzzyTermQuery::new(term, fuzzy.get_distance(), fuzzy.transposition)
+            };
+            (match_mode.occur(), Box::new(query) as Box<dyn Query>)
+        })
+        .collect();
+
+    let fuzzy_query = ConstScoreQuery::new(
+        Box::new(BooleanQuery::new(fuzzy_queries)),
+        FUZZY_MATCH_SCORE,
+    );
+
+    Ok(Box::new(BooleanQuery::new(vec![
+        (Occur::Should, exact_query),
+        (Occur::Should, Box::new(fuzzy_query)),
+    ])))
+}
+
+/**
+ * Runs a glob or regex against the untokenized lowercase name, so it has to match the whole name
+ */
+fn name_pattern_query(field: Field, name_pattern: &NamePattern) -> tantivy::Result<Box<dyn Query>> {
+    let regex = match name_pattern.syntax {
+        PatternSyntax::Glob => glob_to_regex(&name_pattern.pattern),
+        PatternSyntax::Regex => format!("(?i){}", strip_regex_anchors(&name_pattern.pattern)),
+    };
+    Ok(Box::new(RegexQuery::from_pattern(&regex, field)?))
+}
+
+/**
+ * Term dictionary regexes are implicitly anchored and reject explicit anchors. A trailing `$` is
+ * only an anchor when the backslashes before it don't escape it
+ */
+fn strip_regex_anchors(pattern: &str) -> &str {
+    let pattern = pattern.strip_prefix('^').unwrap_or(pattern);
+    match pattern.strip_suffix('$') {
+        Some(stripped) => {
+            let backslashes = stripped.chars().rev().take_while(|c| *c == '\\').count();
+            if backslashes % 2 == 0 {
+                stripped
+            } else {
+                pattern
+            }
+        }
+        None => pattern,
+    }
+}
+
+/**
+ * Matches files whose whole name is `query_str`, ignoring case and the quotes that the query
+ * language puts around terms
+ */
+fn exact_name_query(name_raw_field: Field, query_str: &str, bonus: f32) -> Option<Box<dyn Query>> {
+    let name = query_str.trim().trim_matches('"').to_lowercase();
+    if name.is_empty() {
+        return None;
+    }
+    let term = Term::from_field_text(name_raw_field, &name);
+    let query = TermQuery::new(term, IndexRecordOption::Basic);
+    Some(Box::new(ConstScoreQuery::new(Box::new(query), bonus)))
+}
+
+/**
+ * In `All` mode every word of the query string has to match, rather than any one of them
+ */
+/**
+ * Matches the words of the name as typed, or as the start of words when searching by prefix
+ */
+fn name_query(
+    searcher: &Searcher,
+    field: Field,
+    query_str: &str,
+    search_params: &SearchParamsModel,
+) -> tantivy::Result<Box<dyn Query>> {
+    if search_params.prefix {
+        prefix_name_query(searcher.index(), field, query_str)
+    } else {
+        let query_parser = text_query_parser(searcher.index(), field, search_params.match_mode);
+        Ok(query_parser.parse_query(query_str)?)
+    }
+}
+
+fn text_query_parser(index: &Index, field: Field, match_mode: MatchMode) -> QueryParser {
+    let mut query_parser = QueryParser::for_index(index, vec![field]);
+    if match_mode == MatchMode::All {
+        query_parser.set_conjunction_by_default();
+    }
+    query_parser
+}
+
+/**
+ * Search-as-you-type query: every token of the name must match, but the last one only has to be
+ * the start of an indexed term since the user is most likely still typing it
+ */
+fn prefix_name_query(
+    index: &Index,
+    field: Field,
+    query_str: &str,
+) -> tantivy::Result<Box<dyn Query>> {
+    let terms = name_terms(index, field, query_str)?;
+    let partial_last = ends_with_partial_token(query_str);
+    let last = terms.len().saturating_sub(1);
+
+    let clauses: Vec<(Occur, Box<dyn Query>)> = terms
+        .into_iter()
+        .enumerate()
+        .map(|(i, term)| {
+            let query: Box<dyn Query> = if i == last && partial_last {
+                Box::new(FuzzyTermQuery::new_prefix(term, 0, false))
+            } else {
+                Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs))
+            };
+            (Occur::Must, query)
+        })
+        .collect();
+
+    Ok(Box::new(BooleanQuery::new(clauses)))
+}
+
+/**
+ * Runs the query string through the same tokenizer that the field was indexed with
+ */
+fn name_terms(index: &Index, field: Field, query_str: &str) -> tantivy::Result<Vec<Term>> {
+    let mut terms = Vec::new();
+    let mut tokenizer = index.tokenizer_for_field(field)?;
+    let mut token_stream = tokenizer.token_stream(query_str);
+    token_stream.process(&mut |token| {
+        terms.push(Term::from_field_text(field, &token.text));
+    });
+    Ok(terms)
+}
+
+// A trailing space means that the user has finished typing the last word
+fn ends_with_partial_token(query_str: &str) -> bool {
+    !query_str.ends_with(char::is_whitespace)
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::{
+        doc,
+        schema::{Schema, Value, FAST, INDEXED, STORED, TEXT},
+        Index,
+    };
+
+    use super::*;
+    use crate::{
+        filesindex::infrastructure::searchindex::schemas::file_schema::create_schema,
+        shared::{
+            index_maintenance::derived_fields::add_derived_fields,
+            models::search_params_model::{SortField, SortOptions},
+            tokenizers::filename_tokenizer::register_filename_tokenizer,
+        },
+    };
+
+    fn test_searcher(files: &[(&str, &str)]) -> Searcher {
+        let schema = create_schema();
+        let index = Index::create_in_ram(schema.clone());
+        register_filename_tokenizer(&index);
+        let mut writer = index.writer::<TantivyDocument>(15_000_000).unwrap();
+        for (name, file_path) in files {
+            let extension = name.rsplit_once('.').map_or("", |(_, ext)| ext);
+            let mut doc = doc!(
+                schema.get_field("name").unwrap() => *name,
+                schema.get_field("path").unwrap() => *file_path,
+                schema.get_field("extension").unwrap() => extension,
+            );
+            add_derived_fields(&mut doc, &schema, name, file_path);
+            writer.add_document(doc).unwrap();
+        }
+        writer.commit().unwrap();
+        index.reader().unwrap().searcher()
+    }
+
+    fn matching_names(searcher: &Searcher, search_params: &SearchParamsModel) -> Vec<String> {
+        let query = build_file_query(searcher, search_params, &FieldBoosts::default()).unwrap();
+        let hits = search_files(searcher, &query, search_params, &ScoreWeights::default()).unwrap();
+        let name_field = searcher.schema().get_field("name").unwrap();
+        let mut names: Vec<String> = hits
+            .docs
+            .into_iter()
+            .map(|(_, doc_address)| {
+                let doc: TantivyDocument = searcher.doc(doc_address).unwrap();
+                doc.get_first(name_field)
+                    .and_then(|value| value.as_str())
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn any_mode_name_still_filters_alongside_extensions() {
+        let searcher = test_searcher(&[
+            ("report.pdf", "/docs/report.pdf"),
+            ("invoice.pdf", "/docs/invoice.pdf"),
+            ("report.txt", "/docs/report.txt"),
+        ]);
+        let search_params = SearchParamsModel {
+            name: Some("report".to_string()),
+            extensions: Some(vec!["pdf".to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(
+            matching_names(&searcher, &search_params),
+            vec!["report.pdf"]
+        );
+    }
+
+    #[test]
+    fn any_mode_matches_either_name_or_path() {
+        let searcher = test_searcher(&[
+            ("report.pdf", "/docs/report.pdf"),
+            ("notes.txt", "/archive/notes.txt"),
+            ("invoice.pdf", "/docs/invoice.pdf"),
+        ]);
+        let search_params = SearchParamsModel {
+            name: Some("report".to_string()),
+            file_path: Some("archive".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            matching_names(&searcher, &search_params),
+            vec!["notes.txt", "report.pdf"]
+        );
+    }
+
+    #[test]
+    fn index_from_before_the_newer_fields_can_still_be_searched() {
+        // The schema the index was created with before name_raw, extension, category and size
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("file_id", TEXT | STORED);
+        schema_builder.add_text_field("name", TEXT | STORED);
+        schema_builder.add_date_field("date_modified", INDEXED | STORED);
+        schema_builder.add_text_field("metadata", TEXT | STORED);
+        schema_builder.add_text_field("path", TEXT | STORED);
+        schema_builder.add_f64_field("popularity", FAST | STORED);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema.clone());
+        let mut writer = index.writer::<TantivyDocument>(15_000_000).unwrap();
+        for (name, file_path) in [
+            ("report.pdf", "/docs/report.pdf"),
+            ("notes.txt", "/docs/notes.txt"),
+        ] {
+            writer
+                .add_document(doc!(
+                    schema.get_field("name").unwrap() => name,
+                    schema.get_field("path").unwrap() => file_path,
+                ))
+                .unwrap();
+        }
+        writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let search_params = SearchParamsModel {
+            name: Some("report".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            matching_names(&searcher, &search_params),
+            vec!["report.pdf"]
+        );
+
+        let sorted_params = SearchParamsModel {
+            file_path: Some("docs".to_string()),
+            sort_by: Some(SortOptions {
+                field: SortField::Name,
+                direction: Default::default(),
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            matching_names(&searcher, &sorted_params),
+            vec!["notes.txt", "report.pdf"]
+        );
+    }
+
+    #[test]
+    fn exact_name_matches_outrank_popular_fuzzy_matches() {
+        let schema = create_schema();
+        let index = Index::create_in_ram(schema.clone());
+        register_filename_tokenizer(&index);
+        let mut writer = index.writer::<TantivyDocument>(15_000_000).unwrap();
+        for (name, popularity) in [("report.pdf", 1.0), ("reprot.pdf", 1_000_000.0)] {
+            let file_path = format!("/docs/{}", name);
+            let mut doc = doc!(
+                schema.get_field("name").unwrap() => name,
+                schema.get_field("path").unwrap() => file_path.as_str(),
+                schema.get_field("popularity").unwrap() => popularity,
+            );
+            add_derived_fields(&mut doc, &schema, name, &file_path);
+            writer.add_document(doc).unwrap();
+        }
+        writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let ranked_names = |offset: usize, limit: usize| {
+            let search_params = SearchParamsModel {
+                name: Some("report".to_string()),
+                fuzzy: Some(FuzzyOptions {
+                    distance: 1,
+                    transposition: true,
+                }),
+                offset: Some(offset),
+                limit: Some(limit),
+                ..Default::default()
+            };
+            let query =
+                build_file_query(&searcher, &search_params, &FieldBoosts::default()).unwrap();
+            let hits =
+                search_files(&searcher, &query, &search_params, &ScoreWeights::default()).unwrap();
+            assert_eq!(hits.total_hits, 2);
+            let name_field = schema.get_field("name").unwrap();
+            hits.docs
+                .into_iter()
+                .map(|(_, doc_address)| {
+                    let doc: TantivyDocument = searcher.doc(doc_address).unwrap();
+                    doc.get_first(name_field)
+                        .and_then(|value| value.as_str())
+                        .unwrap()
+                        .to_string()
+                })
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(ranked_names(0, 10), vec!["report.pdf", "reprot.pdf"]);
+        assert_eq!(ranked_names(1, 1), vec!["reprot.pdf"]);
+    }
+
+    #[test]
+    fn only_unescaped_regex_anchors_are_stripped() {
+        assert_eq!(strip_regex_anchors("^report.*\\.pdf$"), "report.*\\.pdf");
+        assert_eq!(strip_regex_anchors("price\\$"), "price\\$");
+        // An escaped backslash followed by the anchor
+        assert_eq!(strip_regex_anchors("dir\\\\$"), "dir\\\\");
+        assert_eq!(strip_regex_anchors("dir\\\\\\$"), "dir\\\\\\$");
+        assert_eq!(strip_regex_anchors("$"), "");
+    }
+}
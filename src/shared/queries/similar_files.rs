@@ -0,0 +1,57 @@
+use tantivy::{
+    collector::TopDocs,
+    query::{BooleanQuery, BoostQuery, MoreLikeThisQuery, Occur, Query},
+    schema::{Field, OwnedValue},
+    DocAddress, Searcher, TantivyDocument,
+};
+
+use crate::shared::models::field_boosts::FieldBoosts;
+
+/**
+ * Finds the files whose name, path and metadata share the most distinctive words with the document
+ * at `doc_address`, such as `report (1).docx` and `report - Copy.docx` in different folders.
+ * The document itself is left out
+ */
+pub fn similar_files(
+    searcher: &Searcher,
+    doc_address: DocAddress,
+    field_boosts: &FieldBoosts,
+    limit: usize,
+) -> tantivy::Result<Vec<(f32, DocAddress)>> {
+    let schema = searcher.schema();
+    let doc: TantivyDocument = searcher.doc(doc_address)?;
+
+    // Each field gets its own query, so that a shared word in the name counts for more
+    let mut queries: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+    for (field_name, boost) in [
+        ("name", field_boosts.name),
+        ("path", field_boosts.path),
+        ("metadata", field_boosts.metadata),
+    ] {
+        let field = schema.get_field(field_name)?;
+        let values = field_values(&doc, field);
+        if values.is_empty() {
+            continue;
+        }
+        let query = MoreLikeThisQuery::builder()
+            // Words that only this file has can't match any other file
+            .with_min_doc_frequency(2)
+            .with_min_term_frequency(1)
+            .with_document_fields(vec![(field, values)]);
+        let query = BoostQuery::new(Box::new(query), boost);
+        queries.push((Occur::Should, Box::new(query)));
+    }
+    if queries.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let query = BooleanQuery::new(queries);
+    let mut top_docs = searcher.search(&query, &TopDocs::with_limit(limit + 1))?;
+    top_docs.retain(|(_, address)| *address != doc_address);
+    top_docs.truncate(limit);
+    Ok(top_docs)
+}
+
+fn field_values(doc: &TantivyDocument, field: Field) -> Vec<OwnedValue> {
+    doc.get_all(field).cloned().collect()
+}
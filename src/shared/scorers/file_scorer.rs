@@ -0,0 +1,43 @@
+use std::sync::Arc;
+
+use tantivy::{fastfield::FastFieldReaders, DocId, Score};
+
+use crate::shared::{
+    models::score_weights::ScoreWeights,
+    scorers::{
+        pop_scorer::PopularityScorer, recency_scorer::RecencyScorer, size_scorer::SizeScorer,
+        weighted_scorer::WeightedScorer,
+    },
+};
+
+pub const DEFAULT_SCORER: &str = "weighted";
+pub const SCORER_NAMES: [&str; 4] = ["weighted", "popularity", "recency", "size"];
+
+/**
+ * Adjusts the score of a document in one segment, given its text relevance score
+ */
+pub type SegmentScorer = Box<dyn Fn(DocId, Score) -> f64>;
+
+/**
+ * A ranking function applied on top of the BM25 score when results are ordered by relevance
+ */
+pub trait FileScorer: Send + Sync {
+    /**
+     * Called once per segment, so that the fast fields the scorer reads are only opened once
+     */
+    fn for_segment(&self, fast_fields: &FastFieldReaders) -> SegmentScorer;
+}
+
+/**
+ * Looks up one of the `SCORER_NAMES`, ignoring case
+ */
+pub fn scorer_by_name(name: &str, weights: ScoreWeights) -> Option<Arc<dyn FileScorer>> {
+    let scorer: Arc<dyn FileScorer> = match name.to_lowercase().as_str() {
+        "weighted" => Arc::new(WeightedScorer::new(weights)),
+        "popularity" => Arc::new(PopularityScorer::new(weights)),
+        "recency" => Arc::new(RecencyScorer::new(weights)),
+        "size" => Arc::new(SizeScorer::new(weights)),
+        _ => return None,
+    };
+    Some(scorer)
+}
@@ -0,0 +1,48 @@
+use tantivy::fastfield::FastFieldReaders;
+
+use crate::shared::{
+    models::score_weights::ScoreWeights,
+    scorers::file_scorer::{FileScorer, SegmentScorer},
+};
+
+/**
+ * Every file starts at a popularity of 1, and each time it is opened adds 1
+ */
+pub fn popularity_from_usage(usage_count: i64) -> f64 {
+    1.0 + usage_count.max(0) as f64
+}
+
+/**
+ * Files that have never been opened have a popularity of 1, which adds nothing
+ */
+pub fn popularity_score(popularity: f64) -> f64 {
+    popularity.log(10.0)
+}
+
+/**
+ * Ranks by relevance and `log10(popularity)` alone, leaving out recency
+ */
+pub struct PopularityScorer {
+    weights: ScoreWeights,
+}
+
+impl PopularityScorer {
+    pub fn new(weights: ScoreWeights) -> Self {
+        Self { weights }
+    }
+}
+
+impl FileScorer for PopularityScorer {
+    fn for_segment(&self, fast_fields: &FastFieldReaders) -> SegmentScorer {
+        let popularity_field = fast_fields.f64("popularity").ok();
+        let weights = self.weights;
+        Box::new(move |doc, original_score| {
+            let popularity = popularity_field
+                .as_ref()
+                .and_then(|field| field.first(doc))
+                .unwrap_or(1.0);
+            let popularity = popularity_score(popularity);
+            weights.combine(original_score, popularity, 0.0)
+        })
+    }
+}
@@ -0,0 +1,53 @@
+use tantivy::{fastfield::FastFieldReaders, time::OffsetDateTime, DateTime};
+
+use crate::shared::{
+    models::score_weights::ScoreWeights,
+    scorers::file_scorer::{FileScorer, SegmentScorer},
+};
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/**
+ * Decays from 1 for a file modified just now towards 0, halving every `half_life_days`. Dates in
+ * the future count as now
+ */
+pub fn recency_score(date_modified: DateTime, now: DateTime, half_life_days: f64) -> f64 {
+    let age_secs = (now.into_timestamp_secs() - date_modified.into_timestamp_secs()).max(0);
+    let age_days = age_secs as f64 / SECONDS_PER_DAY;
+    0.5_f64.powf(age_days / half_life_days.max(f64::MIN_POSITIVE))
+}
+
+/**
+ * Ranks by relevance and how recently the file was modified, leaving out popularity
+ */
+pub struct RecencyScorer {
+    weights: ScoreWeights,
+    now: DateTime,
+}
+
+impl RecencyScorer {
+    pub fn new(weights: ScoreWeights) -> Self {
+        Self {
+            weights,
+            now: DateTime::from_utc(OffsetDateTime::now_utc()),
+        }
+    }
+}
+
+impl FileScorer for RecencyScorer {
+    fn for_segment(&self, fast_fields: &FastFieldReaders) -> SegmentScorer {
+        // Older indexes don't store the date as a fast field, so nothing counts as recent
+        let date_modified_field = fast_fields.date("date_modified").ok();
+        let weights = self.weights;
+        let now = self.now;
+        Box::new(move |doc, original_score| {
+            let recency = date_modified_field
+                .as_ref()
+                .and_then(|field| field.first(doc))
+                .map_or(0.0, |date_modified| {
+                    recency_score(date_modified, now, weights.recency_half_life_days)
+                });
+            weights.combine(original_score, 0.0, recency)
+        })
+    }
+}
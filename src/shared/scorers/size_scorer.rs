@@ -0,0 +1,37 @@
+use tantivy::fastfield::FastFieldReaders;
+
+use crate::shared::{
+    models::score_weights::ScoreWeights,
+    scorers::file_scorer::{FileScorer, SegmentScorer},
+};
+
+const BYTES_PER_MB: f64 = (1 << 20) as f64;
+
+/**
+ * Puts the largest files first, which is what you want when looking for what takes up space.
+ * Adds `log10(1 + size in MB)`, so a 1 GB file gets about 3 and anything under 1 MB almost nothing
+ */
+pub struct SizeScorer {
+    weights: ScoreWeights,
+}
+
+impl SizeScorer {
+    pub fn new(weights: ScoreWeights) -> Self {
+        Self { weights }
+    }
+}
+
+impl FileScorer for SizeScorer {
+    fn for_segment(&self, fast_fields: &FastFieldReaders) -> SegmentScorer {
+        // Older indexes have no size, which ranks every file as empty
+        let size_field = fast_fields.u64("size").ok();
+        let relevance_weight = self.weights.relevance;
+        Box::new(move |doc, original_score| {
+            let size = size_field
+                .as_ref()
+                .and_then(|field| field.first(doc))
+                .unwrap_or(0) as f64;
+            relevance_weight * original_score as f64 + (1.0 + size / BYTES_PER_MB).log(10.0)
+        })
+    }
+}
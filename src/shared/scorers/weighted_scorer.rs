@@ -0,0 +1,52 @@
+use tantivy::{fastfield::FastFieldReaders, time::OffsetDateTime, DateTime};
+
+use crate::shared::{
+    models::score_weights::ScoreWeights,
+    scorers::{
+        file_scorer::{FileScorer, SegmentScorer},
+        pop_scorer::popularity_score,
+        recency_scorer::recency_score,
+    },
+};
+
+/**
+ * The default ranking: relevance, popularity and recency added up according to the `ScoreWeights`
+ */
+pub struct WeightedScorer {
+    weights: ScoreWeights,
+    now: DateTime,
+}
+
+impl WeightedScorer {
+    pub fn new(weights: ScoreWeights) -> Self {
+        Self {
+            weights,
+            now: DateTime::from_utc(OffsetDateTime::now_utc()),
+        }
+    }
+}
+
+impl FileScorer for WeightedScorer {
+    fn for_segment(&self, fast_fields: &FastFieldReaders) -> SegmentScorer {
+        // Missing from indexes with an older schema, which are still searched while being rebuilt
+        let popularity_field = fast_fields.f64("popularity").ok();
+        let date_modified_field = fast_fields.date("date_modified").ok();
+        let weights = self.weights;
+        let now = self.now;
+        Box::new(move |doc, original_score| {
+            // Default to 1 if no popularity
+            let popularity = popularity_field
+                .as_ref()
+                .and_then(|field| field.first(doc))
+                .unwrap_or(1.0);
+            let popularity = popularity_score(popularity);
+            let recency = date_modified_field
+                .as_ref()
+                .and_then(|field| field.first(doc))
+                .map_or(0.0, |date_modified| {
+                    recency_score(date_modified, now, weights.recency_half_life_days)
+                });
+            weights.combine(original_score, popularity, recency)
+        })
+    }
+}
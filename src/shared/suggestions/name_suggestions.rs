@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use tantivy::{schema::IndexRecordOption, DocSet, Searcher, SegmentReader, Term, TERMINATED};
+
+use crate::shared::{dtos::suggestion_dto::SuggestionDTO, scorers::pop_scorer::popularity_score};
+
+// Only the most frequent completions have their postings read to weigh in popularity
+const CANDIDATE_LIMIT: usize = 50;
+
+/**
+ * Completes the last word of `partial` from the words in file names. Every file containing a
+ * completion adds `1 + log10(popularity)` to its score, so common words in popular files come first
+ */
+pub fn suggest_names(
+    searcher: &Searcher,
+    partial: &str,
+    limit: usize,
+) -> tantivy::Result<Vec<SuggestionDTO>> {
+    let field = searcher.schema().get_field("name")?;
+    let Some(prefix) = last_token(searcher, partial)? else {
+        return Ok(Vec::new());
+    };
+
+    let mut doc_freqs: HashMap<String, u32> = HashMap::new();
+    for segment_reader in searcher.segment_readers() {
+        let inverted_index = segment_reader.inverted_index(field)?;
+        let mut stream = inverted_index
+            .terms()
+            .range()
+            .ge(prefix.as_bytes())
+            .into_stream()?;
+        while stream.advance() {
+            if !stream.key().starts_with(prefix.as_bytes()) {
+                break;
+            }
+            let term = String::from_utf8_lossy(stream.key()).into_owned();
+            *doc_freqs.entry(term).or_default() += stream.value().doc_freq;
+        }
+    }
+
+    let mut candidates: Vec<(String, u32)> = doc_freqs.into_iter().collect();
+    candidates.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    candidates.truncate(CANDIDATE_LIMIT);
+
+    let mut suggestions = Vec::with_capacity(candidates.len());
+    for (text, _) in candidates {
+        let term = Term::from_field_text(field, &text);
+        let mut count: u64 = 0;
+        let mut score = 0.0;
+        for segment_reader in searcher.segment_readers() {
+            let (segment_count, segment_score) = weigh_term(segment_reader, &term)?;
+            count += segment_count;
+            score += segment_score;
+        }
+        // Every file with the word was deleted
+        if count > 0 {
+            suggestions.push(SuggestionDTO {
+                term: text,
+                count,
+                score,
+            });
+        }
+    }
+
+    suggestions.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.term.cmp(&b.term))
+    });
+    suggestions.truncate(limit);
+    Ok(suggestions)
+}
+
+/**
+ * Counts the live documents of one segment containing `term`, along with their summed weight
+ */
+fn weigh_term(segment_reader: &SegmentReader, term: &Term) -> tantivy::Result<(u64, f64)> {
+    let inverted_index = segment_reader.inverted_index(term.field())?;
+    let Some(mut postings) = inverted_index.read_postings(term, IndexRecordOption::Basic)? else {
+        return Ok((0, 0.0));
+    };
+    let popularity_field = segment_reader.fast_fields().f64("popularity")?;
+    let alive_bitset = segment_reader.alive_bitset();
+
+    let mut count: u64 = 0;
+    let mut score = 0.0;
+    let mut doc = postings.doc();
+    while doc != TERMINATED {
+        if alive_bitset.is_none_or(|bitset| bitset.is_alive(doc)) {
+            // Default to 1 if no popularity
+            let popularity = popularity_field.first(doc).unwrap_or(1.0);
+            count += 1;
+            score += 1.0 + popularity_score(popularity);
+        }
+        doc = postings.advance();
+    }
+    Ok((count, score))
+}
+
+// Tokenized like the names themselves, so `myRep` completes `rep`
+fn last_token(searcher: &Searcher, partial: &str) -> tantivy::Result<Option<String>> {
+    let field = searcher.schema().get_field("name")?;
+    let mut tokenizer = searcher.index().tokenizer_for_field(field)?;
+    let mut token_stream = tokenizer.token_stream(partial);
+    let mut last = None;
+    token_stream.process(&mut |token| last = Some(token.text.clone()));
+    Ok(last)
+}
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+use tantivy::{schema::Field, Searcher, Term};
+
+// How many corrected queries are offered at most
+const CORRECTION_LIMIT: usize = 3;
+// Words this short only get corrections one edit away, since two edits would change most of them
+const SHORT_WORD_LEN: usize = 4;
+
+/**
+ * Offers corrected versions of `query_str`, best first, in which every word that no file name
+ * contains is replaced by the closest word that one does. Returns nothing if every word is known.
+ *
+ * Only words sharing the first letter are considered, so that the whole term dictionary isn't
+ * scanned for each misspelled word. Typos in the first letter are rare enough to make up for it
+ */
+pub fn spelling_corrections(searcher: &Searcher, query_str: &str) -> tantivy::Result<Vec<String>> {
+    let field = searcher.schema().get_field("name")?;
+    let words = name_words(searcher, field, query_str)?;
+
+    let mut candidates_per_word: Vec<Vec<String>> = Vec::with_capacity(words.len());
+    let mut has_misspelling = false;
+    for word in &words {
+        if doc_freq(searcher, field, word)? > 0 {
+            candidates_per_word.push(vec![word.clone()]);
+            continue;
+        }
+        let candidates = closest_words(searcher, field, word)?;
+        if candidates.is_empty() {
+            // Nothing close enough, so the word is kept as typed
+            candidates_per_word.push(vec![word.clone()]);
+        } else {
+            has_misspelling = true;
+            candidates_per_word.push(candidates);
+        }
+    }
+    if !has_misspelling {
+        return Ok(Vec::new());
+    }
+
+    // The n-th correction uses the n-th closest word for every misspelling that has one
+    let mut corrections: Vec<String> = Vec::new();
+    for rank in 0..CORRECTION_LIMIT {
+        let correction = candidates_per_word
+            .iter()
+            .map(|candidates| candidates[rank.min(candidates.len() - 1)].as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !corrections.contains(&correction) {
+            corrections.push(correction);
+        }
+    }
+    Ok(corrections)
+}
+
+/**
+ * The words within the edit distance allowed for `word`, closest first and then the most common
+ */
+fn closest_words(searcher: &Searcher, field: Field, word: &str) -> tantivy::Result<Vec<String>> {
+    let Some(first_char) = word.chars().next() else {
+        return Ok(Vec::new());
+    };
+    let max_distance = if word.chars().count() <= SHORT_WORD_LEN {
+        1
+    } else {
+        2
+    };
+    let word_chars: Vec<char> = word.chars().collect();
+    let mut first_char_bytes = [0; 4];
+    let prefix = first_char.encode_utf8(&mut first_char_bytes).as_bytes();
+
+    // Each word with its distance and how many files contain it
+    let mut matches: HashMap<String, (usize, u32)> = HashMap::new();
+    for segment_reader in searcher.segment_readers() {
+        let inverted_index = segment_reader.inverted_index(field)?;
+        let mut stream = inverted_index.terms().range().ge(prefix).into_stream()?;
+        while stream.advance() {
+            if !stream.key().starts_with(prefix) {
+                break;
+            }
+            let Ok(term) = std::str::from_utf8(stream.key()) else {
+                continue;
+            };
+            let term_chars: Vec<char> = term.chars().collect();
+            if term_chars.len().abs_diff(word_chars.len()) > max_distance {
+                continue;
+            }
+            let distance = edit_distance(&word_chars, &term_chars);
+            if distance <= max_distance {
+                let entry = matches.entry(term.to_string()).or_insert((distance, 0));
+                entry.1 += stream.value().doc_freq;
+            }
+        }
+    }
+
+    let mut matches: Vec<(String, (usize, u32))> = matches.into_iter().collect();
+    matches.sort_by(|(a, (a_distance, a_freq)), (b, (b_distance, b_freq))| {
+        a_distance
+            .cmp(b_distance)
+            .then_with(|| b_freq.cmp(a_freq))
+            .then_with(|| a.cmp(b))
+    });
+    Ok(matches
+        .into_iter()
+        .take(CORRECTION_LIMIT)
+        .map(|(term, _)| term)
+        .collect())
+}
+
+/**
+ * Levenshtein distance where swapping two neighbouring letters counts as a single edit, the same
+ * way the fuzzy name search counts it
+ */
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+fn doc_freq(searcher: &Searcher, field: Field, word: &str) -> tantivy::Result<u64> {
+    searcher.doc_freq(&Term::from_field_text(field, word))
+}
+
+// Tokenized like the names themselves, so the words can be looked up as they are
+fn name_words(searcher: &Searcher, field: Field, query_str: &str) -> tantivy::Result<Vec<String>> {
+    let mut tokenizer = searcher.index().tokenizer_for_field(field)?;
+    let mut token_stream = tokenizer.token_stream(query_str);
+    let mut words = Vec::new();
+    token_stream.process(&mut |token| words.push(token.text.clone()));
+    Ok(words)
+}
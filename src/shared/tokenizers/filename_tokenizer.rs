@@ -0,0 +1,127 @@
+use tantivy::{
+    tokenizer::{
+        AsciiFoldingFilter, LowerCaser, RemoveLongFilter, TextAnalyzer, Token, TokenStream,
+        Tokenizer,
+    },
+    Index,
+};
+
+pub const FILENAME_TOKENIZER: &str = "filename";
+
+/**
+ * Splits file names and paths into words, including the words hidden inside camelCase names and
+ * letter/digit runs, so `myReportFinal_v2.backup.xlsx` becomes `my report final v 2 backup xlsx`
+ */
+#[derive(Clone, Default)]
+pub struct FilenameTokenizer {
+    token: Token,
+}
+
+pub struct FilenameTokenStream<'a> {
+    text: &'a str,
+    spans: std::vec::IntoIter<(usize, usize)>,
+    token: &'a mut Token,
+}
+
+impl Tokenizer for FilenameTokenizer {
+    type TokenStream<'a> = FilenameTokenStream<'a>;
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> FilenameTokenStream<'a> {
+        self.token.reset();
+        FilenameTokenStream {
+            text,
+            spans: split_words(text).into_iter(),
+            token: &mut self.token,
+        }
+    }
+}
+
+impl<'a> TokenStream for FilenameTokenStream<'a> {
+    fn advance(&mut self) -> bool {
+        self.token.text.clear();
+        self.token.position = self.token.position.wrapping_add(1);
+        match self.spans.next() {
+            Some((offset_from, offset_to)) => {
+                self.token.offset_from = offset_from;
+                self.token.offset_to = offset_to;
+                self.token.text.push_str(&self.text[offset_from..offset_to]);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn token(&self) -> &Token {
+        self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.token
+    }
+}
+
+/**
+ * The analyzer used for the `name` and `path` fields. Words are lowercased and folded to ASCII so
+ * that `Résumé.pdf` can be found by typing `resume`
+ */
+pub fn filename_analyzer() -> TextAnalyzer {
+    TextAnalyzer::builder(FilenameTokenizer::default())
+        .filter(RemoveLongFilter::limit(40))
+        .filter(LowerCaser)
+        .filter(AsciiFoldingFilter)
+        .build()
+}
+
+/**
+ * Tokenizers are not persisted with the index, so this has to be called every time an index is
+ * created or opened
+ */
+pub fn register_filename_tokenizer(index: &Index) {
+    index
+        .tokenizers()
+        .register(FILENAME_TOKENIZER, filename_analyzer());
+}
+
+// Returns the byte ranges of every word in the text
+fn split_words(text: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut word_start: Option<usize> = None;
+    let mut prev: Option<char> = None;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((offset, c)) = chars.next() {
+        if !c.is_alphanumeric() {
+            if let Some(start) = word_start.take() {
+                spans.push((start, offset));
+            }
+            prev = None;
+            continue;
+        }
+        match (word_start, prev) {
+            (Some(start), Some(prev)) => {
+                let next = chars.peek().map(|(_, next)| *next);
+                if is_word_boundary(prev, c, next) {
+                    spans.push((start, offset));
+                    word_start = Some(offset);
+                }
+            }
+            _ => word_start = Some(offset),
+        }
+        prev = Some(c);
+    }
+    if let Some(start) = word_start {
+        spans.push((start, text.len()));
+    }
+    spans
+}
+
+// camel|Case, XML|File, v|2, 2024|Report
+fn is_word_boundary(prev: char, current: char, next: Option<char>) -> bool {
+    if prev.is_numeric() != current.is_numeric() {
+        return true;
+    }
+    if prev.is_lowercase() && current.is_uppercase() {
+        return true;
+    }
+    // The last capital of an acronym starts the next word
+    prev.is_uppercase() && current.is_uppercase() && next.is_some_and(|next| next.is_lowercase())
+}
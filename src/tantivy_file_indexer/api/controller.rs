@@ -0,0 +1,109 @@
+use std::sync::Arc;
+
+use actix_web::{
+    web::{self, ServiceConfig},
+    HttpResponse, Responder,
+};
+use futures::future;
+
+use crate::{
+    shared::models::search_params_model::SearchParamsModel,
+    tantivy_file_indexer::{
+        dtos::file_opened_dto_input::FileOpenedDTOInput,
+        models::hybrid_params_model::HybridParamsModel,
+        services::{local_db::service::SqlxService, search_index::service::SearchIndexService},
+    },
+};
+
+pub struct SearchController {
+    search_service: Arc<SearchIndexService>,
+    sqlx_service: Arc<SqlxService>,
+}
+
+impl SearchController {
+    pub fn new(search_service: Arc<SearchIndexService>, sqlx_service: Arc<SqlxService>) -> Self {
+        Self {
+            search_service,
+            sqlx_service,
+        }
+    }
+
+    async fn query(self: Arc<Self>, dto: web::Json<SearchParamsModel>) -> impl Responder {
+        match self.search_service.query(&dto) {
+            Ok(result) => HttpResponse::Ok().json(result),
+            Err(err) => {
+                println!("failed to execute query: {}", err);
+                HttpResponse::BadRequest().body(format!("Failed to execute query: {}", err))
+            }
+        }
+    }
+
+    async fn hybrid_query(self: Arc<Self>, dto: web::Json<HybridParamsModel>) -> impl Responder {
+        // The vevtor service isn't built yet, so there are no semantic hits to merge in
+        let semantic_search = future::ready(Ok(Vec::new()));
+        match self
+            .search_service
+            .hybrid_query(&dto, semantic_search)
+            .await
+        {
+            Ok(result) => HttpResponse::Ok().json(result),
+            Err(err) => {
+                println!("failed to execute hybrid query: {}", err);
+                HttpResponse::BadRequest().body(format!("Failed to execute hybrid query: {}", err))
+            }
+        }
+    }
+
+    /**
+     * Counts the open towards the file's popularity, which the popularity updater writes into the
+     * index later
+     */
+    async fn file_opened(self: Arc<Self>, dto: web::Json<FileOpenedDTOInput>) -> impl Responder {
+        match self
+            .sqlx_service
+            .files_table()
+            .increment_usage(&dto.file_path)
+            .await
+        {
+            Ok(()) => HttpResponse::Ok().finish(),
+            Err(err) => {
+                println!("failed to record file open: {}", err);
+                HttpResponse::InternalServerError()
+                    .body(format!("Failed to record file open: {}", err))
+            }
+        }
+    }
+
+    pub fn map_routes(self: Arc<Self>, cfg: &mut ServiceConfig) {
+        cfg.route(
+            "/query",
+            web::post().to({
+                let self_clone = Arc::clone(&self); // Cloning the Arc to avoid moving
+                move |dto| {
+                    let self_clone = Arc::clone(&self_clone);
+                    async move { self_clone.query(dto).await }
+                }
+            }),
+        )
+        .route(
+            "/query/hybrid",
+            web::post().to({
+                let self_clone = Arc::clone(&self); // Cloning the Arc to avoid moving
+                move |dto| {
+                    let self_clone = Arc::clone(&self_clone);
+                    async move { self_clone.hybrid_query(dto).await }
+                }
+            }),
+        )
+        .route(
+            "/files/opened",
+            web::post().to({
+                let self_clone = Arc::clone(&self); // Cloning the Arc to avoid moving
+                move |dto| {
+                    let self_clone = Arc::clone(&self_clone);
+                    async move { self_clone.file_opened(dto).await }
+                }
+            }),
+        );
+    }
+}
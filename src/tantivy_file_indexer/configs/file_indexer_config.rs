@@ -1,8 +1,14 @@
 use std::path::PathBuf;
 
+use crate::shared::models::{field_boosts::FieldBoosts, score_weights::ScoreWeights};
+
 #[derive(Clone)]
 pub struct FileIndexerConfig {
     pub buffer_size: usize,
     pub indexer_batch_size: usize,
     pub app_path: PathBuf,
+    /// How results are ranked when a search doesn't set its own weights
+    pub score_weights: ScoreWeights,
+    /// How much a hit in the name, path or metadata counts towards the relevance
+    pub field_boosts: FieldBoosts,
 }
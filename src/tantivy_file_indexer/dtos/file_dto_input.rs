@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::shared::models::file_category::FileCategory;
+
 
 #[derive(Clone, Deserialize, Serialize)]
 #[serde(rename_all = "PascalCase")]
@@ -10,5 +12,8 @@ pub struct FileDTOInput {
     pub metadata: String,
     pub date_modified: u64, // UNIX timestamp
     pub popularity:f64,
+    pub extension: String,
+    pub category: FileCategory,
+    pub size: u64, // bytes
 }
 
@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct FileOpenedDTOInput {
+    /// The path of the file that was opened from the search results
+    pub file_path: String,
+}
@@ -1,4 +1,7 @@
 pub mod service_container;
+pub mod api {
+    pub mod controller;
+}
 pub mod configs {
     pub mod file_indexer_config;
 }
@@ -7,6 +10,7 @@ mod schemas {
 }
 mod dtos {
     pub mod file_dto_input;
+    pub mod file_opened_dto_input;
 }
 mod util {
     pub mod file_id_helper;
@@ -18,7 +22,9 @@ mod converters {
 pub mod services {
     pub mod search_index {
         mod core {
+            pub mod hybrid_search;
             pub mod index_worker;
+            pub mod popularity_updater;
             pub mod querier;
         }
         pub mod models {
@@ -63,5 +69,5 @@ pub mod services {
     }
 }
 mod models {
-    pub mod search_params_model;
+    pub mod hybrid_params_model;
 }
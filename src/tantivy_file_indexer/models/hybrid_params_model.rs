@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+
+use crate::shared::models::fusion_weights::FusionWeights;
+
+use crate::shared::models::search_params_model::{SearchParamsModel, MAX_LIMIT};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct HybridParamsModel {
+    /// The keyword search. Its limit and offset page through the merged results
+    pub params: SearchParamsModel,
+    /// The text embedded for the semantic search. Defaults to the name being searched for
+    pub semantic_query: Option<String>,
+    pub fusion_weights: Option<FusionWeights>,
+}
+
+impl HybridParamsModel {
+    pub fn get_semantic_query(&self) -> Option<&str> {
+        self.semantic_query
+            .as_deref()
+            .or(self.params.name.as_deref())
+            .filter(|x| !x.trim().is_empty())
+    }
+
+    /**
+     * The number of merged results to skip. Neither search returns more than `MAX_LIMIT` candidates,
+     * so the page has to end within them
+     */
+    pub fn get_offset(&self) -> usize {
+        self.params
+            .offset
+            .unwrap_or(0)
+            .min(MAX_LIMIT - self.params.get_limit())
+    }
+
+    /**
+     * How many results each search returns, so that every file on the requested page could come
+     * from either one
+     */
+    pub fn get_candidate_limit(&self) -> usize {
+        self.get_offset() + self.params.get_limit()
+    }
+
+    /**
+     * The keyword search fetches the candidates ordered by relevance. Paging happens after merging
+     */
+    pub fn keyword_params(&self) -> SearchParamsModel {
+        SearchParamsModel {
+            limit: Some(self.get_candidate_limit()),
+            offset: None,
+            sort_by: None,
+            ..self.params.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_end_within_the_candidates() {
+        let params = HybridParamsModel {
+            params: SearchParamsModel {
+                limit: Some(50),
+                offset: Some(5000),
+                ..Default::default()
+            },
+            semantic_query: None,
+            fusion_weights: None,
+        };
+        assert_eq!(params.get_offset(), MAX_LIMIT - 50);
+        assert_eq!(params.get_candidate_limit(), MAX_LIMIT);
+        assert_eq!(params.keyword_params().get_limit(), MAX_LIMIT);
+    }
+}
@@ -1,16 +1,51 @@
-use tantivy::schema::{Schema, FAST, INDEXED, STORED, TEXT};
+use tantivy::schema::{
+    FacetOptions, IndexRecordOption, Schema, TextFieldIndexing, TextOptions, FAST, INDEXED, STORED,
+    STRING, TEXT,
+};
+
+use crate::shared::{
+    index_maintenance::file_key::FILE_KEY_FIELD, tokenizers::filename_tokenizer::FILENAME_TOKENIZER,
+};
 
 pub fn create_schema() -> Schema {
     let mut schema_builder = Schema::builder();
 
+    let filename_options = TextOptions::default()
+        .set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer(FILENAME_TOKENIZER)
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+        )
+        .set_stored();
+
     schema_builder.add_text_field("file_id", TEXT | STORED); // UID
+    // Primary key derived from the path, see `file_key`
+    schema_builder.add_text_field(FILE_KEY_FIELD, STRING);
 
-    schema_builder.add_text_field("name", TEXT | STORED);
-    schema_builder.add_date_field("date_modified", INDEXED | STORED);
+    schema_builder.add_text_field("name", filename_options.clone());
+    schema_builder.add_date_field("date_modified", INDEXED | STORED | FAST);
     schema_builder.add_text_field("metadata", TEXT | STORED);
-    schema_builder.add_text_field("path", TEXT | STORED);
+    schema_builder.add_text_field("path", filename_options);
+    // Lowercase, without the leading dot
+    schema_builder.add_text_field("extension", STRING | STORED | FAST);
+    // One of the `FileCategory` values
+    schema_builder.add_text_field("category", STRING | STORED | FAST);
 
     schema_builder.add_f64_field("popularity", FAST | STORED);
+    schema_builder.add_u64_field("size", INDEXED | FAST | STORED); // bytes
+
+    // Untokenized lowercase copy of the name, used to sort alphabetically and for glob/regex matching
+    let name_raw_options = TextOptions::default()
+        .set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer("raw")
+                .set_index_option(IndexRecordOption::Basic),
+        )
+        .set_fast(Some("raw"));
+    schema_builder.add_text_field("name_raw", name_raw_options);
+    schema_builder.add_u64_field("path_depth", FAST);
+    // The directory that contains the file, e.g. `/C:/Users/me` for `C:\Users\me\notes.txt`
+    schema_builder.add_facet_field("directory", FacetOptions::default());
 
     schema_builder.build()
 }
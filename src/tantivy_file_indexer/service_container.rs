@@ -4,9 +4,10 @@ use super::services::{
     local_db::service::SqlxService,
     search_index::service::SearchIndexService,
 };
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
 use super::configs::file_indexer_config::FileIndexerConfig;
+use crate::shared::models::{field_boosts::FieldBoosts, score_weights::ScoreWeights};
 
 pub struct AppServiceContainer {
     pub search_service: Arc<SearchIndexService>,
@@ -33,6 +34,9 @@ impl AppServiceContainer {
             app_save_service.clone(),
         )
         .await;
+        if search_service.needs_recrawl() {
+            Self::recrawl_known_directories(&sqlx_service, &crawler_service).await;
+        }
 
         Self {
             search_service,
@@ -41,11 +45,29 @@ impl AppServiceContainer {
         }
     }
 
+    /**
+     * Stops crawling and updating popularity, lets the indexer finish and commit what it was sent,
+     * saves the crawler's queue and closes the database, so that nothing crawled so far is lost
+     */
+    pub async fn shutdown(&self) {
+        self.crawler_service.stop().await;
+        self.search_service.stop_popularity_updater().await;
+        if let Err(err) = self.search_service.flush().await {
+            println!("Error committing files: {}", err);
+        }
+        if let Err(err) = self.crawler_service.save_queue().await {
+            println!("Failed to save queue: {}", err);
+        }
+        self.sqlx_service.close().await;
+    }
+
     fn create_file_indexer_config(app_save_service: &Arc<AppSaveService>) -> FileIndexerConfig {
         FileIndexerConfig {
             buffer_size: 50_000_000,
             indexer_batch_size: 256,
             app_path: app_save_service.save_dir.clone(),
+            score_weights: ScoreWeights::default(),
+            field_boosts: FieldBoosts::default(),
         }
     }
 
@@ -53,6 +75,24 @@ impl AppServiceContainer {
         Arc::new(SearchIndexService::new(config))
     }
 
+    /**
+     * The database outlives the index, so it knows where to crawl to fill a new one
+     */
+    async fn recrawl_known_directories(
+        sqlx_service: &Arc<SqlxService>,
+        crawler_service: &Arc<FileCrawlerService>,
+    ) {
+        match sqlx_service.files_table().get_root_directories().await {
+            Ok(directories) => {
+                println!("Crawling {} directories again", directories.len());
+                crawler_service
+                    .push_dirs_default(directories.into_iter().map(PathBuf::from).collect())
+                    .await;
+            }
+            Err(err) => println!("Error getting directories to crawl again: {}", err),
+        }
+    }
+
     fn initialize_app_save_service(save_dir: AppSavePath, app_name: &str) -> Arc<AppSaveService> {
         Arc::new(AppSaveService::new(save_dir, app_name))
     }
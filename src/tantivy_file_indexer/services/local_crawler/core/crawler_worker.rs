@@ -1,5 +1,5 @@
 use crossbeam::queue::SegQueue;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::time::Instant;
 use std::{path::PathBuf, sync::Arc, time::UNIX_EPOCH};
 use tokio::time::{self, Duration};
@@ -8,19 +8,28 @@ use tokio::{
     task::JoinSet,
 };
 
-use crate::tantivy_file_indexer::{
-    dtos::file_dto_input::FileDTOInput,
-    services::search_index::models::index_worker::file_input::FileInputModel, util::file_id_helper,
+use crate::{
+    shared::models::file_category::{file_extension, FileCategory},
+    tantivy_file_indexer::{
+        dtos::file_dto_input::FileDTOInput,
+        services::search_index::models::index_worker::file_input::FileInputModel,
+        util::file_id_helper,
+    },
 };
 
 use super::crawler_queue::CrawlerQueue;
 
 // Note that the crawler does not handle database operations
+/**
+ * Crawls directories from the queue until it is empty or `stop_requested` is set. Directories that
+ * are being read when it is set are still sent over
+ */
 pub async fn spawn_worker(
     sender: mpsc::Sender<FileInputModel>,
     max_concurrent_tasks: usize,
     save_queue_after: usize,
     queue: Arc<CrawlerQueue>,
+    stop_requested: Arc<AtomicBool>,
 ) {
     let dir_entries = Arc::new(SegQueue::new());
     let semaphore = Arc::new(Semaphore::new(max_concurrent_tasks));
@@ -29,6 +38,11 @@ pub async fn spawn_worker(
     let worker_queue = Arc::clone(&queue);
 
     loop {
+        // Every task has been joined by now
+        if stop_requested.load(Ordering::Relaxed) {
+            println!("Worker stopped");
+            break;
+        }
         if let Some(path) = queue.pop().await {
             let dir_entries = Arc::clone(&dir_entries);
             let semaphore = Arc::clone(&semaphore);
@@ -117,13 +131,22 @@ async fn create_dto(entry: &tokio::fs::DirEntry) -> Result<FileDTOInput, String>
         .expect("Time went backwards")
         .as_secs();
 
-    let file_id = if entry.path().is_dir() {
+    let is_dir = entry.path().is_dir();
+    let file_id = if is_dir {
         //for directories, use the directory path since getting their ID is more difficult
         entry.path().to_string_lossy().to_string()
     } else {
         file_id_helper::get_file_id(entry.path().to_path_buf())?
     };
 
+    // A directory named `photos.2023` does not have an extension
+    let extension = if is_dir {
+        String::new()
+    } else {
+        file_extension(&entry.path())
+    };
+    let category = FileCategory::from_extension(&extension);
+
     let dto = FileDTOInput {
         file_id,
         name: entry.file_name().to_string_lossy().to_string(),
@@ -131,6 +154,9 @@ async fn create_dto(entry: &tokio::fs::DirEntry) -> Result<FileDTOInput, String>
         metadata: "test metadata".to_string(),
         date_modified: unix_timestamp,
         popularity: 1.0,
+        extension,
+        category,
+        size: if is_dir { 0 } else { metadata.len() },
     };
     Ok(dto)
 }
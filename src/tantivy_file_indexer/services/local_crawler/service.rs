@@ -1,11 +1,12 @@
-use tokio::sync::mpsc;
+use tokio::{sync::mpsc, task::JoinHandle};
 
 use crate::tantivy_file_indexer::services::app_save::service::AppSaveService;
 use crate::tantivy_file_indexer::services::local_db::service::SqlxService;
 use crate::tantivy_file_indexer::services::search_index::models::index_worker::file_input::FileInputModel;
 use crate::tantivy_file_indexer::services::search_index::service::SearchIndexService;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
 
 use super::core::crawler_queue::{CrawlerQueue, Priority};
 
@@ -15,6 +16,8 @@ pub struct FileCrawlerService {
     queue: Arc<CrawlerQueue>,
     search_service: Arc<SearchIndexService>,
     db_service: Arc<SqlxService>,
+    stop_requested: Arc<AtomicBool>,
+    worker: StdMutex<Option<JoinHandle<()>>>,
 }
 
 impl FileCrawlerService {
@@ -32,6 +35,8 @@ impl FileCrawlerService {
             search_service,
             db_service,
             queue,
+            stop_requested: Arc::new(AtomicBool::new(false)),
+            worker: StdMutex::new(None),
         }
     }
 
@@ -39,16 +44,40 @@ impl FileCrawlerService {
         let queue = self.queue.clone();
         let max_concurrent_tasks = self.max_concurrent_tasks;
         let crawler_save_after_iters = self.crawler_save_after_iters;
+        let stop_requested = self.stop_requested.clone();
 
-        tokio::task::spawn(async move {
+        let worker = tokio::task::spawn(async move {
             super::core::crawler_worker::spawn_worker(
                 sender,
                 max_concurrent_tasks,
                 crawler_save_after_iters,
                 queue,
+                stop_requested,
             )
             .await;
         });
+        *self.worker.lock().unwrap() = Some(worker);
+    }
+
+    /**
+     * Stops crawling new directories and waits for the ones being read to be sent to the indexer.
+     * Dropping the crawler's `Sender` lets the indexer finish
+     */
+    pub async fn stop(&self) {
+        self.stop_requested.store(true, Ordering::Relaxed);
+        let worker = self.worker.lock().unwrap().take();
+        if let Some(worker) = worker {
+            if let Err(err) = worker.await {
+                println!("Crawler task error: {:?}", err);
+            }
+        }
+    }
+
+    /**
+     * Saves the directories that are left to crawl, so that crawling picks up from there next time
+     */
+    pub async fn save_queue(&self) -> Result<(), std::io::Error> {
+        self.queue.save().await
     }
 
     pub async fn push_dirs(&self, paths: Vec<(&str, Priority)>) {
@@ -59,6 +88,15 @@ impl FileCrawlerService {
         self.process_dirs(dirs).await;
     }
 
+    /**
+     * Queues the directories with the default priority, e.g. to fill an index that was recreated
+     */
+    pub async fn push_dirs_default(&self, paths: Vec<PathBuf>) {
+        for path in paths {
+            self.queue.push_default(path).await;
+        }
+    }
+
     pub async fn load_or(&self, fallback_directories: Vec<&str>) {
         let dirs: Vec<PathBuf> = fallback_directories
             .iter()
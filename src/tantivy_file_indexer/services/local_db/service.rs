@@ -25,6 +25,13 @@ impl SqlxService {
     pub fn files_table(&self)->&FilesTable{
         &self.files_table
     }
+    /**
+     * Waits for the queries in progress, then closes every connection. Nothing can be queried after
+     */
+    pub async fn close(&self) {
+        self.pool.lock().await.close().await;
+    }
+
     /**
      * Runs a command on the database to reclaim unused memory
      */
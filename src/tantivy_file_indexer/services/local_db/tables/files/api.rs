@@ -1,4 +1,9 @@
-use std::{borrow::Cow, collections::HashSet, sync::Arc};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    path::Path,
+    sync::Arc,
+};
 
 use sqlx::{Pool, Sqlite};
 use tokio::sync::Mutex;
@@ -6,6 +11,9 @@ use tokio::sync::Mutex;
 use super::models::FileModel;
 
 type RowsAffected = u64;
+
+const MAX_BOUND_PARAMETERS: usize = 500;
+
 pub struct FilesTable {
     pool: Arc<Mutex<Pool<Sqlite>>>,
 }
@@ -25,18 +33,89 @@ impl FilesTable {
         .await
         .unwrap();
 
+        // Databases created before usage tracking don't have the column yet
+        let (has_usage_count,): (bool,) = sqlx::query_as(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('files') WHERE name = 'usage_count'",
+        )
+        .fetch_one(&*pool_locked)
+        .await
+        .unwrap();
+        if !has_usage_count {
+            sqlx::query("ALTER TABLE files ADD COLUMN usage_count INTEGER NOT NULL DEFAULT 0")
+                .execute(&*pool_locked)
+                .await
+                .unwrap();
+        }
+
+        Self::backfill_parent_paths(&pool_locked).await.unwrap();
+        // Directories are listed and removed along with their contents by their parent path
+        sqlx::query("CREATE INDEX IF NOT EXISTS files_parent_path ON files (parent_path)")
+            .execute(&*pool_locked)
+            .await
+            .unwrap();
+
         Self { pool }
     }
 
+    /**
+     * Databases from before the parent paths were saved have none at all, which would leave no root
+     * directories to crawl again. Each path gets its parent, and the parents that weren't crawled
+     * themselves are added as the roots
+     */
+    async fn backfill_parent_paths(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
+        let (has_parent_paths,): (bool,) =
+            sqlx::query_as("SELECT COUNT(*) > 0 FROM files WHERE parent_path IS NOT NULL")
+                .fetch_one(pool)
+                .await?;
+        if has_parent_paths {
+            return Ok(());
+        }
+        let paths: Vec<(String,)> = sqlx::query_as("SELECT path FROM files")
+            .fetch_all(pool)
+            .await?;
+
+        let mut transaction = pool.begin().await?;
+        for (path,) in paths {
+            let parent_path = match Path::new(&path).parent() {
+                Some(parent_path) if !parent_path.as_os_str().is_empty() => {
+                    parent_path.to_string_lossy()
+                }
+                _ => continue,
+            };
+            sqlx::query("INSERT OR IGNORE INTO files (path) VALUES (?)")
+                .bind(parent_path.as_ref())
+                .execute(&mut transaction)
+                .await?;
+            sqlx::query("UPDATE files SET parent_path = ? WHERE path = ?")
+                .bind(parent_path.as_ref())
+                .bind(&path)
+                .execute(&mut transaction)
+                .await?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+
     pub async fn upsert_many(&self, models: &Vec<FileModel>) -> Result<(), sqlx::Error> {
         let pool = self.pool.lock().await;
         let mut transaction = pool.begin().await?;
     
         for model in models {
-            sqlx::query("INSERT OR IGNORE INTO files (path) VALUES (?)")
-                .bind(&model.path)
-                .execute(&mut transaction)
-                .await?;
+            // The parent has to exist for the foreign key, e.g. the root that was crawled
+            if let Some(parent_path) = &model.parent_path {
+                sqlx::query("INSERT OR IGNORE INTO files (path) VALUES (?)")
+                    .bind(parent_path)
+                    .execute(&mut transaction)
+                    .await?;
+            }
+            sqlx::query(
+                "INSERT INTO files (path, parent_path) VALUES (?, ?)
+                    ON CONFLICT(path) DO UPDATE SET parent_path = excluded.parent_path",
+            )
+            .bind(&model.path)
+            .bind(&model.parent_path)
+            .execute(&mut transaction)
+            .await?;
         }
     
         transaction.commit().await?;
@@ -58,16 +137,20 @@ impl FilesTable {
         }
 
         let pool = self.pool.lock().await;
-        let placeholders = paths.iter().map(|_| "?").collect::<Vec<_>>().join(", ");
-        let query = format!("DELETE FROM files WHERE path IN ({})", placeholders);
+        let mut rows_affected = 0;
+        // SQLite limits how many parameters a single statement can bind
+        for chunk in paths.chunks(MAX_BOUND_PARAMETERS) {
+            let placeholders = chunk.iter().map(|_| "?").collect::<Vec<_>>().join(", ");
+            let query = format!("DELETE FROM files WHERE path IN ({})", placeholders);
 
-        let mut query_builder = sqlx::query(&query);
-        for path in &paths {
-            query_builder = query_builder.bind(path);
-        }
+            let mut query_builder = sqlx::query(&query);
+            for path in chunk {
+                query_builder = query_builder.bind(path);
+            }
 
-        let result = query_builder.execute(&*pool).await?;
-        Ok(result.rows_affected())
+            rows_affected += query_builder.execute(&*pool).await?.rows_affected();
+        }
+        Ok(rows_affected)
     }
 
     pub async fn get_paths_from_dir(&self, dir: &str) -> Result<HashSet<String>, sqlx::Error> {
@@ -80,6 +163,101 @@ impl FilesTable {
         Ok(set)
     }
 
+    /**
+     * The directories that crawling started from. Their subdirectories are found again by crawling
+     * them, so they aren't included
+     */
+    pub async fn get_root_directories(&self) -> Result<Vec<String>, sqlx::Error> {
+        let pool = self.pool.lock().await;
+        let rows: Vec<(String,)> = sqlx::query_as(
+            "SELECT DISTINCT parent_path FROM files WHERE parent_path IS NOT NULL
+                AND parent_path NOT IN (SELECT path FROM files WHERE parent_path IS NOT NULL)",
+        )
+        .fetch_all(&*pool)
+        .await?;
+        Ok(rows.into_iter().map(|(path,)| path).collect())
+    }
+
+    /**
+     * The paths along with everything nested inside of them, so that removing a directory doesn't
+     * leave its contents behind
+     */
+    pub async fn get_paths_under(
+        &self,
+        paths: &HashSet<String>,
+    ) -> Result<HashSet<String>, sqlx::Error> {
+        let mut set: HashSet<String> = HashSet::new();
+        let paths: Vec<&String> = paths.iter().collect();
+        let pool = self.pool.lock().await;
+        for chunk in paths.chunks(MAX_BOUND_PARAMETERS) {
+            let placeholders = chunk.iter().map(|_| "?").collect::<Vec<_>>().join(", ");
+            let query = format!(
+                "WITH RECURSIVE nested(path) AS (
+                    SELECT path FROM files WHERE path IN ({})
+                    UNION SELECT files.path FROM files JOIN nested ON files.parent_path = nested.path
+                ) SELECT path FROM nested",
+                placeholders
+            );
+            let mut query_builder = sqlx::query_as::<_, (String,)>(&query);
+            for path in chunk {
+                query_builder = query_builder.bind(*path);
+            }
+            let rows = query_builder.fetch_all(&*pool).await?;
+            set.extend(rows.into_iter().map(|(path,)| path));
+        }
+        Ok(set)
+    }
+
+    /**
+     * Records that the file was opened from the search results. Files that haven't been crawled yet
+     * are added so that the count isn't lost
+     */
+    pub async fn increment_usage(&self, path: &str) -> Result<(), sqlx::Error> {
+        let pool = self.pool.lock().await;
+        sqlx::query(
+            "INSERT INTO files (path, usage_count) VALUES (?, 1)
+                ON CONFLICT(path) DO UPDATE SET usage_count = usage_count + 1",
+        )
+        .bind(path)
+        .execute(&*pool)
+        .await?;
+        Ok(())
+    }
+
+    /**
+     * Returns the usage count of each of the paths that has been opened at least once
+     */
+    pub async fn get_usage_counts<S: AsRef<str>>(
+        &self,
+        paths: &[S],
+    ) -> Result<HashMap<String, i64>, sqlx::Error> {
+        let pool = self.pool.lock().await;
+        let mut usage_counts = HashMap::new();
+
+        // SQLite limits how many parameters a single statement can bind
+        for chunk in paths.chunks(MAX_BOUND_PARAMETERS) {
+            let placeholders = chunk.iter().map(|_| "?").collect::<Vec<_>>().join(", ");
+            let query = format!(
+                "SELECT path, usage_count FROM files WHERE usage_count > 0 AND path IN ({})",
+                placeholders
+            );
+            let mut query_builder = sqlx::query_as::<_, (String, i64)>(&query);
+            for path in chunk {
+                query_builder = query_builder.bind(path.as_ref());
+            }
+            usage_counts.extend(query_builder.fetch_all(&*pool).await?);
+        }
+
+        Ok(usage_counts)
+    }
+
+    pub async fn get_used_files(&self) -> Result<Vec<(String, i64)>, sqlx::Error> {
+        let pool = self.pool.lock().await;
+        sqlx::query_as("SELECT path, usage_count FROM files WHERE usage_count > 0")
+            .fetch_all(&*pool)
+            .await
+    }
+
     pub async fn count_files(&self) -> Result<i64, sqlx::Error> {
         let pool = self.pool.lock().await;
         let row: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM files")
@@ -88,3 +266,51 @@ impl FilesTable {
         Ok(row.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn paths_saved_without_parents_get_them_and_their_roots_back() {
+        // Every connection to an in-memory database opens a new one
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query(
+            "CREATE TABLE files (
+                path TEXT PRIMARY KEY,
+                parent_path TEXT,
+                FOREIGN KEY (parent_path) REFERENCES files(path)
+            ) WITHOUT ROWID;",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        for path in [
+            "/docs/a.txt",
+            "/docs/sub",
+            "/docs/sub/b.txt",
+            "/music/c.mp3",
+        ] {
+            sqlx::query("INSERT INTO files (path) VALUES (?)")
+                .bind(path)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        let files_table = FilesTable::new_async(Arc::new(Mutex::new(pool))).await;
+        let mut roots = files_table.get_root_directories().await.unwrap();
+        roots.sort();
+        assert_eq!(roots, vec!["/docs", "/music"]);
+        assert_eq!(
+            files_table.get_paths_from_dir("/docs/sub").await.unwrap(),
+            HashSet::from(["/docs/sub/b.txt".to_string()])
+        );
+    }
+}
@@ -12,4 +12,16 @@ pub async fn get_num_stored_files(service: State<'_, Arc<SqlxService>>) -> Resul
         Err(err) => Err(err.to_string()),
     }
 }
+
+#[tauri::command]
+pub async fn record_file_opened(
+    path: String,
+    service: State<'_, Arc<SqlxService>>,
+) -> Result<(), String> {
+    service
+        .files_table()
+        .increment_usage(&path)
+        .await
+        .map_err(|err| err.to_string())
+}
 */
\ No newline at end of file
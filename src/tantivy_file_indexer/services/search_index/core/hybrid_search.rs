@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+use tantivy::{
+    collector::TopDocs,
+    query::TermQuery,
+    schema::{IndexRecordOption, Schema},
+    Searcher, TantivyDocument, Term,
+};
+
+use crate::{
+    shared::{
+        dtos::{file_dto::FileDTO, source_scores_dto::SourceScoresDTO},
+        models::fusion_weights::FusionWeights,
+    },
+    tantivy_file_indexer::converters::doc_to_dto::doc_to_dto,
+};
+
+// The vector store only knows file names, and a common name can belong to many files
+const MAX_FILES_PER_NAME: usize = 10;
+
+/**
+ * Merges the keyword results with the semantic hits by reciprocal rank fusion. Semantic hits are
+ * file names, best first, and every indexed file with that name shares the hit's rank.
+ * The merged results are ordered by their fused score, which replaces `FileDTO::score`
+ */
+pub fn fuse(
+    schema: &Schema,
+    searcher: &Searcher,
+    keyword_results: Vec<FileDTO>,
+    semantic_hits: Vec<(String, f32)>,
+    weights: &FusionWeights,
+) -> tantivy::Result<Vec<FileDTO>> {
+    // Keyed by path, in the order the files were first seen
+    let mut fused: Vec<FileDTO> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+
+    for (i, mut file) in keyword_results.into_iter().enumerate() {
+        let rank = i + 1;
+        file.source_scores = Some(SourceScoresDTO {
+            keyword_rank: Some(rank),
+            keyword_score: Some(file.score),
+            ..Default::default()
+        });
+        file.score = weights.keyword_score(rank);
+        positions.insert(file.file_path.clone(), fused.len());
+        fused.push(file);
+    }
+
+    for (i, (name, similarity)) in semantic_hits.into_iter().enumerate() {
+        let rank = i + 1;
+        for file in files_named(schema, searcher, &name)? {
+            let position = *positions.entry(file.file_path.clone()).or_insert_with(|| {
+                fused.push(file);
+                fused.len() - 1
+            });
+            let file = &mut fused[position];
+            let source_scores = file.source_scores.get_or_insert_with(Default::default);
+            // A name can come up more than once, only its best rank counts
+            if source_scores.semantic_rank.is_some() {
+                continue;
+            }
+            source_scores.semantic_rank = Some(rank);
+            source_scores.semantic_score = Some(similarity);
+            let keyword_score = source_scores
+                .keyword_rank
+                .map_or(0.0, |keyword_rank| weights.keyword_score(keyword_rank));
+            file.score = keyword_score + weights.semantic_score(rank);
+        }
+    }
+
+    fused.sort_by(|a, b| b.score.total_cmp(&a.score));
+    Ok(fused)
+}
+
+fn files_named(schema: &Schema, searcher: &Searcher, name: &str) -> tantivy::Result<Vec<FileDTO>> {
+    let field = schema.get_field("name_raw")?;
+    let term = Term::from_field_text(field, &name.to_lowercase());
+    let query = TermQuery::new(term, IndexRecordOption::Basic);
+    searcher
+        .search(&query, &TopDocs::with_limit(MAX_FILES_PER_NAME))?
+        .into_iter()
+        .map(|(score, doc_address)| {
+            let doc: TantivyDocument = searcher.doc(doc_address)?;
+            Ok(doc_to_dto(doc, schema, score as f64))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::{doc, Index};
+
+    use super::*;
+    use crate::{
+        filesindex::infrastructure::searchindex::schemas::file_schema::create_schema,
+        shared::{
+            index_maintenance::derived_fields::add_derived_fields, queries::file_lookup::find_file,
+            tokenizers::filename_tokenizer::register_filename_tokenizer,
+        },
+    };
+
+    fn test_searcher(files: &[(&str, &str)]) -> Searcher {
+        let schema = create_schema();
+        let index = Index::create_in_ram(schema.clone());
+        register_filename_tokenizer(&index);
+        let mut writer = index.writer::<TantivyDocument>(15_000_000).unwrap();
+        for (name, file_path) in files {
+            let mut doc = doc!(
+                schema.get_field("name").unwrap() => *name,
+                schema.get_field("path").unwrap() => *file_path,
+            );
+            add_derived_fields(&mut doc, &schema, name, file_path);
+            writer.add_document(doc).unwrap();
+        }
+        writer.commit().unwrap();
+        index.reader().unwrap().searcher()
+    }
+
+    fn keyword_result(searcher: &Searcher, file_path: &str, score: f64) -> FileDTO {
+        let doc_address = find_file(searcher, file_path).unwrap().unwrap();
+        doc_to_dto(searcher.doc(doc_address).unwrap(), searcher.schema(), score)
+    }
+
+    #[test]
+    fn ranks_are_fused_by_their_weighted_reciprocals() {
+        let searcher = test_searcher(&[
+            ("report.pdf", "/docs/report.pdf"),
+            ("notes.txt", "/docs/notes.txt"),
+            ("budget.xlsx", "/docs/budget.xlsx"),
+        ]);
+        let keyword_results = vec![
+            keyword_result(&searcher, "/docs/report.pdf", 7.0),
+            keyword_result(&searcher, "/docs/notes.txt", 3.0),
+        ];
+        // A name that comes up again keeps its best rank
+        let semantic_hits = vec![
+            ("budget.xlsx".to_string(), 0.9),
+            ("notes.txt".to_string(), 0.8),
+            ("Notes.txt".to_string(), 0.5),
+        ];
+        let weights = FusionWeights {
+            keyword: 1.0,
+            semantic: 2.0,
+            rank_constant: 10.0,
+        };
+
+        let fused = fuse(
+            searcher.schema(),
+            &searcher,
+            keyword_results,
+            semantic_hits,
+            &weights,
+        )
+        .unwrap();
+
+        let paths: Vec<&str> = fused.iter().map(|file| file.file_path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["/docs/notes.txt", "/docs/budget.xlsx", "/docs/report.pdf"]
+        );
+        let scores: Vec<f64> = fused.iter().map(|file| file.score).collect();
+        assert_eq!(
+            scores,
+            vec![1.0 / 12.0 + 2.0 / 12.0, 2.0 / 11.0, 1.0 / 11.0]
+        );
+
+        let notes = fused[0].source_scores.as_ref().unwrap();
+        assert_eq!(notes.keyword_rank, Some(2));
+        assert_eq!(notes.keyword_score, Some(3.0));
+        assert_eq!(notes.semantic_rank, Some(2));
+        assert_eq!(notes.semantic_score, Some(0.8));
+        let budget = fused[1].source_scores.as_ref().unwrap();
+        assert_eq!(budget.keyword_rank, None);
+        assert_eq!(budget.semantic_rank, Some(1));
+    }
+}
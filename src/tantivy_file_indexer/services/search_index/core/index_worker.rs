@@ -6,16 +6,25 @@ use std::{
 };
 
 use super::super::models::index_worker::file_input::FileInputModel;
-use crate::tantivy_file_indexer::{
-    converters::date_converter::unix_time_to_tantivy_datetime,
-    dtos::file_dto_input::FileDTOInput,
-    services::local_db::{service::SqlxService, tables::files::models::FileModel},
+use crate::{
+    shared::{
+        index_maintenance::{derived_fields::add_derived_fields, file_key::file_key_term},
+        scorers::pop_scorer::popularity_from_usage,
+    },
+    tantivy_file_indexer::{
+        converters::date_converter::unix_time_to_tantivy_datetime,
+        dtos::file_dto_input::FileDTOInput,
+        services::local_db::{service::SqlxService, tables::files::models::FileModel},
+    },
 };
 use tantivy::{doc, schema::Schema, IndexWriter, TantivyError};
 use tokio::sync::{mpsc, Mutex};
 
 /**
- * waits around for the MPSC channel to send it files to index, in which it will index them
+ * waits around for the MPSC channel to send it files to index, in which it will index them.
+ *
+ * Files are committed every `batch_size` files, or after `commit_interval` if fewer than that are
+ * waiting. Whatever is left is committed once every sender has been dropped
  */
 pub async fn spawn_worker(
     mut receiver: mpsc::Receiver<FileInputModel>,
@@ -23,10 +32,31 @@ pub async fn spawn_worker(
     schema: Arc<Schema>,
     db_service: Arc<SqlxService>,
     batch_size: usize,
+    commit_interval: Duration,
 ) {
     let mut batches_processed: usize = 0;
-    // Each call to 'next' will return every file/directory path as a DTO
-    while let Some(model) = receiver.recv().await {
+    let mut commit_timer = tokio::time::interval(commit_interval);
+    // The first tick completes right away
+    commit_timer.tick().await;
+
+    loop {
+        // Each call to 'next' will return every file/directory path as a DTO
+        let model = tokio::select! {
+            model = receiver.recv() => match model {
+                Some(model) => model,
+                None => break,
+            },
+            _ = commit_timer.tick() => {
+                // The end of a crawl rarely fills a whole batch
+                if batches_processed > 0 {
+                    if let Err(err) = commit_and_retry(writer.clone()).await {
+                        println!("Error committing files: {}", err);
+                    }
+                    batches_processed = 0;
+                }
+                continue;
+            }
+        };
         let seen_paths: HashSet<String> = model.dtos.iter().map(|x| x.file_path.clone()).collect();
 
         let dtos_len = model.dtos.len();
@@ -43,7 +73,7 @@ pub async fn spawn_worker(
             println!("Error processing files: {}", err)
         }
 
-        if let Err(err) = remove_unseen_entries(
+        match remove_unseen_entries(
             model.directory_from,
             seen_paths,
             Arc::clone(&writer),
@@ -52,7 +82,8 @@ pub async fn spawn_worker(
         )
         .await
         {
-            println!("Error removing stale entries: {}", err);
+            Ok(num_removed) => batches_processed += num_removed,
+            Err(err) => println!("Error removing stale entries: {}", err),
         }
 
         if batches_processed >= batch_size {
@@ -60,6 +91,13 @@ pub async fn spawn_worker(
                 println!("Error committing files: {}", err);
             }
             batches_processed = 0;
+            commit_timer.reset();
+        }
+    }
+
+    if batches_processed > 0 {
+        if let Err(err) = commit_and_retry(writer.clone()).await {
+            println!("Error committing files: {}", err);
         }
     }
     println!("receiver channel closed");
@@ -72,26 +110,41 @@ async fn process_files(
     schema: Arc<Schema>,
     db_service: Arc<SqlxService>,
 ) -> Result<(), String> {
+    // Opened files keep their popularity when they are crawled again
+    let paths: Vec<&str> = dtos.iter().map(|x| x.file_path.as_str()).collect();
+    let usage_counts = db_service
+        .files_table()
+        .get_usage_counts(&paths)
+        .await
+        .map_err(|x| format!("Error getting usage counts: {}", x))?;
+
     let writer = writer.lock().await;
     // Remove from index and add document within a single lock
 
     let mut db_file_models: Vec<FileModel> = Vec::new();
 
     for dto in dtos.into_iter() {
-        writer.delete_term(tantivy::Term::from_field_text(
-            schema
-                .get_field("file_id")
+        writer.delete_term(
+            file_key_term(&schema, &dto.file_path)
                 .map_err(|x| format!("Field doesn't exist: {}", x))?,
-            &dto.file_path,
-        ));
-        writer.add_document(doc! {
+        );
+        let name = dto.name.clone();
+        let popularity = usage_counts
+            .get(&dto.file_path)
+            .map_or(dto.popularity, |count| popularity_from_usage(*count));
+        let mut document = doc! {
         schema.get_field("file_id").unwrap() => dto.file_id,
         schema.get_field("name").unwrap() => dto.name,
         schema.get_field("date_modified").unwrap() => unix_time_to_tantivy_datetime(dto.date_modified),
         schema.get_field("path").unwrap() => dto.file_path.clone(),
         schema.get_field("metadata").unwrap() => dto.metadata,
-        schema.get_field("popularity").unwrap() => dto.popularity,
-        }).map_err(|x| format!("Failed to add document: {}",x))?;
+        schema.get_field("popularity").unwrap() => popularity,
+        schema.get_field("extension").unwrap() => dto.extension,
+        schema.get_field("category").unwrap() => dto.category.as_str(),
+        schema.get_field("size").unwrap() => dto.size,
+        };
+        add_derived_fields(&mut document, &schema, &name, &dto.file_path);
+        writer.add_document(document).map_err(|x| format!("Failed to add document: {}",x))?;
 
         // Create model for DTO but dont add it to DB
         let path_clone = dto.file_path.clone();
@@ -123,7 +176,15 @@ async fn remove_unseen_entries(
         .await
         .map_err(|e| e.to_string())?;
 
-    let stale_paths: HashSet<_> = stored_paths.difference(&seen_paths).cloned().collect();
+    let unseen_paths: HashSet<_> = stored_paths.difference(&seen_paths).cloned().collect();
+    if unseen_paths.is_empty() {
+        return Ok(0);
+    }
+    let stale_paths = db_service
+        .files_table()
+        .get_paths_under(&unseen_paths)
+        .await
+        .map_err(|e| e.to_string())?;
     let stale_paths_len = stale_paths.len();
 
     if let Err(err) = remove_files_from_index(&stale_paths, writer.clone(), schema).await {
@@ -136,7 +197,7 @@ async fn remove_unseen_entries(
     Ok(stale_paths_len)
 }
 
-async fn commit_and_retry(writer: Arc<Mutex<IndexWriter>>) -> Result<(), TantivyError> {
+pub async fn commit_and_retry(writer: Arc<Mutex<IndexWriter>>) -> Result<(), TantivyError> {
     let retry_attempts = 3;
 
     for attempt in 1..=retry_attempts {
@@ -161,10 +222,9 @@ where
     T: IntoIterator<Item = S>,
     S: AsRef<str>,
 {
-    let field = schema.get_field("file_id")?;
     let writer = writer.lock().await;
     for path in file_paths {
-        writer.delete_term(tantivy::Term::from_field_text(field, path.as_ref()));
+        writer.delete_term(file_key_term(schema, path.as_ref())?);
     }
 
     Ok(())
@@ -175,3 +235,105 @@ fn get_parent_path(path: String) -> Option<String> {
         .parent()
         .map(|val| val.to_string_lossy().to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tantivy::{collector::Count, query::TermQuery, schema::IndexRecordOption, Index};
+
+    use super::*;
+    use crate::{
+        shared::{
+            models::file_category::FileCategory,
+            tokenizers::filename_tokenizer::register_filename_tokenizer,
+        },
+        tantivy_file_indexer::{
+            schemas::file_schema::create_schema,
+            services::app_save::service::{AppSavePath, AppSaveService},
+        },
+    };
+
+    fn file_dto(file_path: &str) -> FileDTOInput {
+        let name = Path::new(file_path).file_name().unwrap().to_string_lossy();
+        FileDTOInput {
+            file_id: file_path.to_string(),
+            name: name.to_string(),
+            file_path: file_path.to_string(),
+            metadata: String::new(),
+            date_modified: 0,
+            popularity: 1.0,
+            extension: "txt".to_string(),
+            category: FileCategory::from_extension("txt"),
+            size: 0,
+        }
+    }
+
+    fn crawled(directory: &str, file_paths: &[&str]) -> FileInputModel {
+        FileInputModel {
+            dtos: file_paths.iter().map(|path| file_dto(path)).collect(),
+            directory_from: PathBuf::from(directory),
+        }
+    }
+
+    fn count_key(searcher: &tantivy::Searcher, schema: &Schema, file_path: &str) -> usize {
+        let term = file_key_term(schema, file_path).unwrap();
+        let query = TermQuery::new(term, IndexRecordOption::Basic);
+        searcher.search(&query, &Count).unwrap()
+    }
+
+    #[tokio::test]
+    async fn crawling_again_replaces_files_and_removes_the_ones_that_are_gone() {
+        let save_dir =
+            std::env::temp_dir().join(format!("index-worker-test-{}", std::process::id()));
+        let app_name = "DesktopSearch";
+        // The save service would otherwise create its directory in the working directory
+        fs::create_dir_all(save_dir.join(app_name)).unwrap();
+        let app_save_service = AppSaveService::new(
+            AppSavePath::Other(save_dir.to_string_lossy().to_string()),
+            app_name,
+        );
+        let db_service = Arc::new(SqlxService::new_async(&app_save_service).await);
+
+        let schema = create_schema();
+        let index = Index::create_in_ram(schema.clone());
+        register_filename_tokenizer(&index);
+        let writer = Arc::new(Mutex::new(index.writer(15_000_000).unwrap()));
+
+        let (sender, receiver) = mpsc::channel(8);
+        sender
+            .send(crawled("/docs", &["/docs/a.txt", "/docs/b.txt"]))
+            .await
+            .unwrap();
+        sender
+            .send(crawled("/docs", &["/docs/a.txt"]))
+            .await
+            .unwrap();
+        drop(sender);
+        spawn_worker(
+            receiver,
+            writer,
+            Arc::new(schema.clone()),
+            db_service.clone(),
+            128,
+            Duration::from_secs(30),
+        )
+        .await;
+
+        let searcher = index.reader().unwrap().searcher();
+        assert_eq!(count_key(&searcher, &schema, "/docs/a.txt"), 1);
+        assert_eq!(count_key(&searcher, &schema, "/docs/b.txt"), 0);
+        assert_eq!(searcher.num_docs(), 1);
+        assert_eq!(
+            db_service
+                .files_table()
+                .get_paths_from_dir("/docs")
+                .await
+                .unwrap(),
+            HashSet::from(["/docs/a.txt".to_string()])
+        );
+
+        db_service.close().await;
+        fs::remove_dir_all(save_dir).unwrap();
+    }
+}
@@ -0,0 +1,246 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use tantivy::{
+    schema::{Field, Schema, Value},
+    Document, IndexReader, IndexWriter, TantivyDocument,
+};
+use tokio::sync::{Mutex, Notify};
+
+use crate::{
+    shared::{
+        index_maintenance::{derived_fields::add_derived_fields, file_key::file_key_term},
+        queries::file_lookup::find_file,
+        scorers::pop_scorer::popularity_from_usage,
+    },
+    tantivy_file_indexer::services::local_db::service::SqlxService,
+};
+
+/**
+ * Every `interval`, writes the usage counts recorded in the database into the `popularity` field of
+ * the files that were opened, so that they surface first. Only the counts that changed since the
+ * last time are written, and nothing is committed when none did. Returns once `stop` is notified
+ */
+pub async fn spawn_updater(
+    reader: Arc<RwLock<IndexReader>>,
+    writer: Arc<Mutex<IndexWriter>>,
+    schema: Arc<Schema>,
+    db_service: Arc<SqlxService>,
+    interval: Duration,
+    stop: Arc<Notify>,
+) {
+    let mut interval = tokio::time::interval(interval);
+    // The usage counts that are already in the index
+    let mut applied_counts: HashMap<String, i64> = HashMap::new();
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = stop.notified() => break,
+        }
+        let changed_counts = match db_service.files_table().get_used_files().await {
+            Ok(used_files) => used_files
+                .into_iter()
+                .filter(|(path, usage_count)| applied_counts.get(path) != Some(usage_count))
+                .collect::<Vec<(String, i64)>>(),
+            Err(err) => {
+                println!("Error getting usage counts: {}", err);
+                continue;
+            }
+        };
+        if changed_counts.is_empty() {
+            continue;
+        }
+
+        let reader = reader.clone();
+        let writer = writer.clone();
+        let schema = schema.clone();
+        // Committing and searching block, so they get their own thread
+        let result = tokio::task::spawn_blocking(move || {
+            apply_usage_counts(&reader, &writer, &schema, changed_counts)
+        })
+        .await
+        .map_err(|x| x.to_string())
+        .and_then(|result| result);
+        match result {
+            Ok((applied, num_updated)) => {
+                if num_updated > 0 {
+                    println!("Updated the popularity of {} files", num_updated);
+                }
+                applied_counts.extend(applied);
+            }
+            Err(err) => println!("Error updating popularity: {}", err),
+        }
+    }
+}
+
+/**
+ * Returns the counts that no longer have to be written, along with the number of documents that
+ * were replaced. Files that aren't indexed yet get their popularity when the indexer adds them
+ */
+fn apply_usage_counts(
+    reader: &RwLock<IndexReader>,
+    writer: &Mutex<IndexWriter>,
+    schema: &Schema,
+    changed_counts: Vec<(String, i64)>,
+) -> Result<(Vec<(String, i64)>, usize), String> {
+    let popularity_field = schema.get_field("popularity").map_err(|x| x.to_string())?;
+    let name_field = schema.get_field("name").map_err(|x| x.to_string())?;
+
+    let mut writer = writer.blocking_lock();
+    // The reader only moves to the new index once a rebuild is done, and the old documents can't be
+    // written into it
+    let reader = reader.read().unwrap().clone();
+    if reader.searcher().schema() != schema {
+        return Ok((Vec::new(), 0));
+    }
+    // Documents still waiting to be committed would otherwise be deleted below without being seen
+    writer.commit().map_err(|x| x.to_string())?;
+    reader.reload().map_err(|x| x.to_string())?;
+    let searcher = reader.searcher();
+
+    let mut num_updated: usize = 0;
+    for (path, usage_count) in &changed_counts {
+        let popularity = popularity_from_usage(*usage_count);
+        let Some(doc_address) = find_file(&searcher, path).map_err(|x| x.to_string())? else {
+            continue;
+        };
+        let doc: TantivyDocument = searcher.doc(doc_address).map_err(|x| x.to_string())?;
+        if doc.get_first(popularity_field).and_then(|x| x.as_f64()) == Some(popularity) {
+            continue;
+        }
+
+        // Fast fields can't be changed in place, so the document is replaced
+        writer.delete_term(file_key_term(schema, path).map_err(|x| x.to_string())?);
+        let name = doc
+            .get_first(name_field)
+            .and_then(|x| x.as_str())
+            .unwrap_or_default();
+        let new_doc = with_popularity(&doc, schema, popularity_field, popularity, name, path);
+        writer.add_document(new_doc).map_err(|x| x.to_string())?;
+        num_updated += 1;
+    }
+
+    if num_updated > 0 {
+        writer.commit().map_err(|x| x.to_string())?;
+    }
+    Ok((changed_counts, num_updated))
+}
+
+/**
+ * Copies the stored fields of `doc` with a new popularity. Fields that aren't stored are derived
+ * again, the same way the reindexer does
+ */
+fn with_popularity(
+    doc: &TantivyDocument,
+    schema: &Schema,
+    popularity_field: Field,
+    popularity: f64,
+    name: &str,
+    file_path: &str,
+) -> TantivyDocument {
+    let mut new_doc = TantivyDocument::new();
+    for (field, value) in doc.iter_fields_and_values() {
+        if field != popularity_field {
+            new_doc.add_field_value(field, value.clone());
+        }
+    }
+    new_doc.add_f64(popularity_field, popularity);
+    add_derived_fields(&mut new_doc, schema, name, file_path);
+    new_doc
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tantivy::{doc, Index};
+
+    use super::*;
+    use crate::{
+        filesindex::infrastructure::searchindex::schemas::file_schema::create_schema,
+        shared::tokenizers::filename_tokenizer::register_filename_tokenizer,
+        tantivy_file_indexer::services::app_save::service::{AppSavePath, AppSaveService},
+    };
+
+    #[test]
+    fn replaces_only_the_documents_whose_popularity_changed() {
+        let schema = create_schema();
+        let index = Index::create_in_ram(schema.clone());
+        register_filename_tokenizer(&index);
+        let mut writer = index.writer::<TantivyDocument>(15_000_000).unwrap();
+        let mut doc = doc!(
+            schema.get_field("name").unwrap() => "report.pdf",
+            schema.get_field("path").unwrap() => "/docs/report.pdf",
+            schema.get_field("popularity").unwrap() => 1.0,
+        );
+        add_derived_fields(&mut doc, &schema, "report.pdf", "/docs/report.pdf");
+        writer.add_document(doc).unwrap();
+        writer.commit().unwrap();
+        let reader = RwLock::new(index.reader().unwrap());
+        let writer = Mutex::new(writer);
+
+        let changed_counts = vec![
+            ("/docs/report.pdf".to_string(), 2),
+            ("/docs/not-indexed.txt".to_string(), 1),
+        ];
+        let (applied, num_updated) =
+            apply_usage_counts(&reader, &writer, &schema, changed_counts.clone()).unwrap();
+        assert_eq!(applied, changed_counts);
+        assert_eq!(num_updated, 1);
+
+        reader.read().unwrap().reload().unwrap();
+        let searcher = reader.read().unwrap().searcher();
+        let doc_address = find_file(&searcher, "/docs/report.pdf").unwrap().unwrap();
+        let doc: TantivyDocument = searcher.doc(doc_address).unwrap();
+        let popularity_field = schema.get_field("popularity").unwrap();
+        assert_eq!(
+            doc.get_first(popularity_field).and_then(|x| x.as_f64()),
+            Some(popularity_from_usage(2))
+        );
+        assert_eq!(searcher.num_docs(), 1);
+
+        let (_, num_updated) =
+            apply_usage_counts(&reader, &writer, &schema, changed_counts).unwrap();
+        assert_eq!(num_updated, 0);
+    }
+
+    #[tokio::test]
+    async fn stops_without_waiting_for_the_next_interval() {
+        let save_dir =
+            std::env::temp_dir().join(format!("popularity-updater-test-{}", std::process::id()));
+        let app_name = "DesktopSearch";
+        // The save service would otherwise create its directory in the working directory
+        fs::create_dir_all(save_dir.join(app_name)).unwrap();
+        let app_save_service = AppSaveService::new(
+            AppSavePath::Other(save_dir.to_string_lossy().to_string()),
+            app_name,
+        );
+        let db_service = Arc::new(SqlxService::new_async(&app_save_service).await);
+
+        let schema = create_schema();
+        let index = Index::create_in_ram(schema.clone());
+        let reader = Arc::new(RwLock::new(index.reader().unwrap()));
+        let writer = Arc::new(Mutex::new(index.writer(15_000_000).unwrap()));
+        let stop = Arc::new(Notify::new());
+        let updater = tokio::spawn(spawn_updater(
+            reader,
+            writer,
+            Arc::new(schema),
+            db_service.clone(),
+            Duration::from_secs(3600),
+            stop.clone(),
+        ));
+
+        stop.notify_one();
+        tokio::time::timeout(Duration::from_secs(5), updater)
+            .await
+            .expect("the updater kept running after being stopped")
+            .unwrap();
+
+        db_service.close().await;
+        fs::remove_dir_all(save_dir).unwrap();
+    }
+}
@@ -1,82 +1,62 @@
-use tantivy::{
-    collector::TopDocs,
-    query::{BooleanQuery, Occur, Query, QueryParser, RangeQuery, TermQuery},
-    schema::Schema,
-    DateTime, Searcher, TantivyDocument, Term,
-};
+use tantivy::{Searcher, TantivyDocument};
 
 use crate::{
-    shared::dtos::file_dto::FileDTO,
-    tantivy_file_indexer::{
-        converters::doc_to_dto::doc_to_dto, models::search_params_model::SearchParamsModel,
+    shared::{
+        dtos::{file_dto::FileDTO, search_results_dto::SearchResultsDTO},
+        models::{
+            field_boosts::FieldBoosts, score_weights::ScoreWeights,
+            search_params_model::SearchParamsModel,
+        },
+        queries::file_query::{
+            build_file_query, corrected_params, did_you_mean, highlights_for_doc, search_files,
+            snippet_generators,
+        },
     },
+    tantivy_file_indexer::converters::doc_to_dto::doc_to_dto,
 };
 
+/**
+ * `default_weights` ranks the results unless the search params bring their own, and
+ * `field_boosts` weighs each text match by the field it was found in
+ */
 pub fn advanced_query(
-    schema: &Schema,
     searcher: &Searcher,
     search_params: &SearchParamsModel,
-) -> tantivy::Result<Vec<FileDTO>> {
-    let mut queries: Vec<(Occur, Box<dyn Query>)> = Vec::new();
-
-    if let Some(file_path) = &search_params.file_path {
-        let field = schema.get_field("path").unwrap();
-        let query_parser = QueryParser::for_index(searcher.index(), vec![field]);
-        let query = query_parser.parse_query(file_path)?;
-        queries.push((Occur::Should, Box::new(query)));
-    }
-
-    if let Some(query_str) = &search_params.name {
-        let field = schema.get_field("name").unwrap();
-        let query_parser = QueryParser::for_index(searcher.index(), vec![field]);
-        let query = query_parser.parse_query(query_str)?;
-        queries.push((Occur::Should, Box::new(query)));
-    }
-
-    if let Some(date_range) = &search_params.date_range {
-        let start_date = DateTime::from_utc(date_range.start);
-        let end_date = DateTime::from_utc(date_range.end);
-        let query = RangeQuery::new_date("date_modified".to_string(), start_date..end_date);
-        queries.push((Occur::Must, Box::new(query)));
-    }
-
-    if let Some(metadata) = &search_params.metadata {
-        let field = schema.get_field("metadata").unwrap();
-        let term = Term::from_field_text(field, metadata);
-        let query = TermQuery::new(term, tantivy::schema::IndexRecordOption::Basic);
-        queries.push((Occur::Must, Box::new(query)));
-    }
-
-    // Combine all the queries into a BooleanQuery
-    let boolean_query = BooleanQuery::new(queries);
-
-    // Execute the query and collect the results
-    let top_docs = searcher.search(
-        &boolean_query,
-        &TopDocs::with_limit(10).tweak_score(|segment_reader: &tantivy::SegmentReader| {
-            let popularity_field = segment_reader
-                .fast_fields()
-                .f64("popularity")
-                .expect("Failed to access popularity field");
-            move |doc, original_score| {
-                // Default to 1 if no popularity
-                let pop_score = popularity_field.first(doc).unwrap_or(1.0);
-                apply_popularity(original_score, pop_score)
-            }
-        }),
-    )?;
-
-    let results: Vec<FileDTO> = top_docs
+    default_weights: &ScoreWeights,
+    field_boosts: &FieldBoosts,
+) -> tantivy::Result<SearchResultsDTO> {
+    let schema = searcher.schema();
+    let boolean_query = build_file_query(searcher, search_params, field_boosts)?;
+    let hits = search_files(searcher, &boolean_query, search_params, default_weights)?;
+    let snippet_generators = snippet_generators(searcher, &boolean_query, search_params)?;
+
+    let results: Vec<FileDTO> = hits
+        .docs
         .into_iter()
         .map(|(_score, doc_address)| {
             let doc: TantivyDocument = searcher.doc(doc_address).unwrap();
-            doc_to_dto(doc, schema, _score)
+            let highlights = highlights_for_doc(&snippet_generators, &doc);
+            let mut dto = doc_to_dto(doc, schema, _score);
+            dto.highlights = highlights;
+            dto
         })
         .collect();
 
-    Ok(results)
-}
+    let did_you_mean = did_you_mean(searcher, search_params, hits.total_hits)?;
+    if let Some((correction, corrected_params)) = corrected_params(search_params, &did_you_mean) {
+        let mut corrected_results =
+            advanced_query(searcher, &corrected_params, default_weights, field_boosts)?;
+        corrected_results.corrected_name = Some(correction);
+        corrected_results.did_you_mean = did_you_mean;
+        return Ok(corrected_results);
+    }
 
-pub fn apply_popularity(existing_score: f32, popularity_score: f64) -> f64 {
-    (existing_score as f64) + popularity_score.log(10.0)
+    Ok(SearchResultsDTO {
+        results,
+        total_hits: hits.total_hits,
+        offset: search_params.get_offset(),
+        aggregations: hits.aggregations,
+        did_you_mean,
+        corrected_name: None,
+    })
 }
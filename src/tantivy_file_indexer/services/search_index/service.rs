@@ -1,23 +1,52 @@
 use crate::{
-    shared::dtos::file_dto::FileDTO,
+    shared::{
+        dtos::{file_dto::FileDTO, search_results_dto::SearchResultsDTO},
+        index_maintenance::{index_rebuilder, reindexer},
+        models::{
+            field_boosts::FieldBoosts, fusion_weights::FusionWeights, score_weights::ScoreWeights,
+            search_params_model::SearchParamsModel,
+        },
+        queries::{file_lookup::find_file, similar_files::similar_files},
+        tokenizers::filename_tokenizer::register_filename_tokenizer,
+    },
     tantivy_file_indexer::{
-        models::search_params_model::SearchParamsModel, services::local_db::service::SqlxService,
+        converters::doc_to_dto::doc_to_dto,
+        models::hybrid_params_model::HybridParamsModel,
+        services::local_db::service::SqlxService,
     },
 };
 
 use super::{
     super::super::{configs::file_indexer_config::FileIndexerConfig, schemas::file_schema},
-    core::{index_worker, querier},
+    core::{hybrid_search, index_worker, popularity_updater, querier},
     models::index_worker::file_input::FileInputModel,
 };
-use std::{fs, sync::Arc};
-use tantivy::{schema::Schema, Index, IndexReader, IndexWriter};
-use tokio::sync::{mpsc::{self, Sender}, Mutex};
+use std::{
+    fs,
+    future::Future,
+    sync::{Arc, Mutex as StdMutex, RwLock},
+    time::Duration,
+};
+use tantivy::{schema::Schema, Index, IndexReader, IndexWriter, Searcher, TantivyDocument};
+use tokio::{
+    sync::{
+        mpsc::{self, Sender},
+        Mutex, Notify,
+    },
+    task::JoinHandle,
+};
 
 pub struct SearchIndexService {
     pub schema: Schema,
     pub index_writer: Arc<Mutex<IndexWriter>>,
-    index_reader: IndexReader,
+    // Swapped for the reader of the new index once a rebuild is done
+    index_reader: Arc<RwLock<IndexReader>>,
+    score_weights: ScoreWeights,
+    field_boosts: FieldBoosts,
+    needs_recrawl: bool,
+    indexer: StdMutex<Option<JoinHandle<()>>>,
+    popularity_updater: StdMutex<Option<JoinHandle<()>>>,
+    stop_popularity_updater: Arc<Notify>,
 }
 
 impl SearchIndexService {
@@ -30,60 +59,299 @@ impl SearchIndexService {
         if !app_path.exists() {
             fs::create_dir_all("DesktopSearch").expect("could not create DesktopSearch directory");
         }
+        reindexer::finish_pending_rebuild(&index_path).expect("could not finish rebuilding index");
         // Create the Tantivy index
-        let index = if index_path.exists() {
+        let index_exists = index_path.exists();
+        let index = if index_exists {
             // If the index directory exists, open the existing index
             println!("Opening existing index at {:?}", index_path);
-            Index::open_in_dir(index_path)
+            Index::open_in_dir(&index_path)
         } else {
             // If the index directory doesn't exist, create a new index
             println!("Creating a new index at {:?}", index_path);
             fs::create_dir_all(index_path.clone()).expect("could not create output directory");
-            Index::create_in_dir(index_path, schema.clone())
+            Index::create_in_dir(&index_path, schema.clone())
         };
-        let index = index.unwrap();
-        let index_writer = index.writer(config.buffer_size).unwrap();
+        // Nothing can be copied out of an index that can't be opened, so the crawler has to fill a
+        // new one
+        let (index, mut needs_recrawl) = match index {
+            Ok(index) => (index, !index_exists),
+            Err(err) => {
+                println!(
+                    "Could not open index at {:?}, creating a new one: {}",
+                    index_path, err
+                );
+                fs::remove_dir_all(&index_path).expect("could not remove unreadable index");
+                fs::create_dir_all(&index_path).expect("could not create output directory");
+                let index = Index::create_in_dir(&index_path, schema.clone()).unwrap();
+                (index, true)
+            }
+        };
+        register_filename_tokenizer(&index);
+        let index_reader = Arc::new(RwLock::new(index.reader().unwrap()));
 
-        let index_reader = index.reader().unwrap();
+        let uncopied_fields = reindexer::uncopied_fields(&index.schema(), &schema);
+        if !uncopied_fields.is_empty() {
+            println!(
+                "Recrawling to fill in fields the old index doesn't have: {}",
+                uncopied_fields.join(", ")
+            );
+            needs_recrawl = true;
+        }
+        let index_writer = index_rebuilder::open_writer(
+            index,
+            &index_path,
+            &schema,
+            config.buffer_size,
+            index_reader.clone(),
+        )
+        .expect("could not open index writer");
 
         Self {
             schema,
-            index_writer: Arc::new(Mutex::new(index_writer)),
+            index_writer,
             index_reader,
+            score_weights: config.score_weights,
+            field_boosts: config.field_boosts,
+            needs_recrawl,
+            indexer: StdMutex::new(None),
+            popularity_updater: StdMutex::new(None),
+            stop_popularity_updater: Arc::new(Notify::new()),
         }
     }
 
+    /**
+     * Whether the index had to be created from scratch, or is being rebuilt from one that lacks
+     * fields, in which case the directories that were crawled before should be crawled again
+     */
+    pub fn needs_recrawl(&self) -> bool {
+        self.needs_recrawl
+    }
+
     /**
      * Returns a `Sender` that a crawler can use to send over files.
      
-     * The `batch_size` indicates how many files are processed before the index writer make a commit
+     * The `batch_size` indicates how many files are processed before the index writer make a commit,
+     * and `commit_interval` how long a smaller batch can wait before it is committed anyway
      */
     pub fn spawn_indexer(
         &self,
         db_service: Arc<SqlxService>,
         batch_size: usize,
         buffer_size: usize,
+        commit_interval: Duration,
     ) -> Sender<FileInputModel> {
         let schema_clone = Arc::new(self.schema.clone());
         let (sender, receiver) = mpsc::channel(buffer_size);
 
         let index_writer_clone = self.index_writer.clone();
 
-        tokio::spawn(async move {
+        let indexer = tokio::spawn(async move {
             index_worker::spawn_worker(
                 receiver,
                 index_writer_clone,
                 schema_clone,
                 db_service,
                 batch_size,
+                commit_interval,
             )
             .await;
         });
+        *self.indexer.lock().unwrap() = Some(indexer);
 
         sender
     }
 
-    pub fn query(&self, params: &SearchParamsModel) -> Result<Vec<FileDTO>, tantivy::TantivyError> {
-        querier::advanced_query(&self.schema, &self.index_reader.searcher(), params)
+    /**
+     * Waits for the indexer to get through the files it was sent, then commits. The indexer only
+     * stops once every `Sender` is dropped, so the crawler has to be stopped first
+     */
+    pub async fn flush(&self) -> tantivy::Result<()> {
+        let indexer = self.indexer.lock().unwrap().take();
+        if let Some(indexer) = indexer {
+            if let Err(err) = indexer.await {
+                println!("Indexer task error: {:?}", err);
+            }
+        }
+        index_worker::commit_and_retry(self.index_writer.clone()).await
+    }
+
+    /**
+     * Periodically feeds the usage counts recorded in the database back into the index, so that
+     * frequently opened files rank higher
+     */
+    pub fn spawn_popularity_updater(&self, db_service: Arc<SqlxService>, interval: Duration) {
+        let schema_clone = Arc::new(self.schema.clone());
+        let index_reader_clone = self.index_reader.clone();
+        let index_writer_clone = self.index_writer.clone();
+        let stop = self.stop_popularity_updater.clone();
+
+        let popularity_updater = tokio::spawn(async move {
+            popularity_updater::spawn_updater(
+                index_reader_clone,
+                index_writer_clone,
+                schema_clone,
+                db_service,
+                interval,
+                stop,
+            )
+            .await;
+        });
+        *self.popularity_updater.lock().unwrap() = Some(popularity_updater);
+    }
+
+    /**
+     * Stops updating the popularity, so that it doesn't write into the index or query the database
+     * while they are shutting down. An update that is already being written is finished first
+     */
+    pub async fn stop_popularity_updater(&self) {
+        let popularity_updater = self.popularity_updater.lock().unwrap().take();
+        if let Some(popularity_updater) = popularity_updater {
+            self.stop_popularity_updater.notify_one();
+            if let Err(err) = popularity_updater.await {
+                println!("Popularity updater task error: {:?}", err);
+            }
+        }
+    }
+
+    /**
+     * While the index is being rebuilt this searches the old one, which still has its old schema
+     */
+    fn searcher(&self) -> Searcher {
+        self.index_reader.read().unwrap().searcher()
+    }
+
+    pub fn query(
+        &self,
+        params: &SearchParamsModel,
+    ) -> Result<SearchResultsDTO, tantivy::TantivyError> {
+        let searcher = self.searcher();
+        querier::advanced_query(
+            &searcher,
+            params,
+            &self.score_weights,
+            &self.field_boosts,
+        )
+    }
+
+    /**
+     * Runs the keyword search alongside `semantic_search`, then merges both rankings with reciprocal
+     * rank fusion. `semantic_search` should resolve to the names of the files whose embeddings are
+     * closest to `params.get_semantic_query()`, best first, such as the ones returned by the vevtor
+     * service. If it fails, the keyword results are returned on their own.
+     *
+     * Semantic hits don't go through the filters of the keyword search
+     */
+    pub async fn hybrid_query<F>(
+        &self,
+        params: &HybridParamsModel,
+        semantic_search: F,
+    ) -> Result<SearchResultsDTO, String>
+    where
+        F: Future<Output = Result<Vec<(String, f32)>, String>>,
+    {
+        let searcher = self.searcher();
+        let keyword_params = params.keyword_params();
+        let score_weights = self.score_weights;
+        let field_boosts = self.field_boosts;
+        // Tantivy searches block, so the keyword search gets its own thread
+        let keyword_search = tokio::task::spawn_blocking(move || {
+            querier::advanced_query(&searcher, &keyword_params, &score_weights, &field_boosts)
+        });
+
+        let (keyword_results, semantic_hits) = tokio::join!(keyword_search, semantic_search);
+        let keyword_results = keyword_results
+            .map_err(|x| x.to_string())?
+            .map_err(|x| x.to_string())?;
+        let semantic_hits = semantic_hits.unwrap_or_else(|err| {
+            println!(
+                "Semantic search failed, using keyword results only: {}",
+                err
+            );
+            Vec::new()
+        });
+
+        let weights = params.fusion_weights.unwrap_or_default();
+        let searcher = self.searcher();
+        let fused = hybrid_search::fuse(
+            searcher.schema(),
+            &searcher,
+            keyword_results.results,
+            semantic_hits,
+            &weights,
+        )
+        .map_err(|x| x.to_string())?;
+
+        // Every keyword match counts, not only the candidates that were fused
+        let semantic_only = fused
+            .iter()
+            .filter(|file| {
+                file.source_scores
+                    .as_ref()
+                    .is_some_and(|scores| scores.keyword_rank.is_none())
+            })
+            .count();
+        let offset = params.get_offset();
+        Ok(SearchResultsDTO {
+            total_hits: keyword_results.total_hits + semantic_only,
+            results: fused
+                .into_iter()
+                .skip(offset)
+                .take(params.params.get_limit())
+                .collect(),
+            offset,
+            aggregations: keyword_results.aggregations,
+            did_you_mean: keyword_results.did_you_mean,
+            corrected_name: keyword_results.corrected_name,
+        })
+    }
+
+    /**
+     * Finds files resembling the one at `file_path` by the words in their name, path and metadata.
+     * `semantic_search` can add the files whose names have embeddings close to this file's name,
+     * such as the ones returned by the vevtor service, merged in by reciprocal rank fusion. It can
+     * resolve to an empty list to only compare words
+     */
+    pub async fn similar_files<F>(
+        &self,
+        file_path: &str,
+        limit: usize,
+        semantic_search: F,
+    ) -> Result<Vec<FileDTO>, String>
+    where
+        F: Future<Output = Result<Vec<(String, f32)>, String>>,
+    {
+        let searcher = self.searcher();
+        let doc_address = find_file(&searcher, file_path)
+            .map_err(|x| x.to_string())?
+            .ok_or_else(|| format!("{} is not indexed", file_path))?;
+        let keyword_results = similar_files(&searcher, doc_address, &self.field_boosts, limit)
+            .and_then(|similar| {
+                similar
+                    .into_iter()
+                    .map(|(score, doc_address)| {
+                        let doc: TantivyDocument = searcher.doc(doc_address)?;
+                        Ok(doc_to_dto(doc, searcher.schema(), score as f64))
+                    })
+                    .collect::<tantivy::Result<Vec<FileDTO>>>()
+            })
+            .map_err(|x| x.to_string())?;
+
+        let semantic_hits = semantic_search.await.unwrap_or_else(|err| {
+            println!("Semantic search failed, comparing words only: {}", err);
+            Vec::new()
+        });
+        let mut fused = hybrid_search::fuse(
+            searcher.schema(),
+            &searcher,
+            keyword_results,
+            semantic_hits,
+            &FusionWeights::default(),
+        )
+        .map_err(|x| x.to_string())?;
+        // The file's own name is the closest one to itself
+        fused.retain(|file| file.file_path != file_path);
+        fused.truncate(limit);
+        Ok(fused)
     }
 }
@@ -4,8 +4,8 @@ use std::sync::Arc;
 use tauri::State;
 
 use crate::{
-    shared::dtos::file_dto::FileDTO,
-    tantivy_file_indexer::models::search_params_model::SearchParamsModel,
+    shared::dtos::search_results_dto::SearchResultsDTO,
+    shared::models::search_params_model::SearchParamsModel,
 };
 
 use super::service::SearchIndexService;
@@ -14,7 +14,7 @@ use super::service::SearchIndexService;
 pub fn search_index_query(
     params: SearchParamsModel,
     service: State<'_, Arc<SearchIndexService>>,
-) -> Result<Vec<FileDTO>, String> {
+) -> Result<SearchResultsDTO, String> {
     match service.query(&params) {
         Ok(result) => Ok(result),
         Err(err) => Err(err.to_string()),